@@ -2,7 +2,6 @@ use chrono::{TimeZone as _, Utc};
 use serde::Serialize;
 use sparklepost::transmission::{
     Attachment, EmailAddress, Message, Options, Recipient, Transmission,
-    TransmissionResponse,
 };
 
 #[derive(Debug, Serialize)]
@@ -28,14 +27,10 @@ fn main() {
         "Example Company",
     ));
 
-    let options = Options {
-        open_tracking: true,
-        click_tracking: true,
-        transactional: false,
-        sandbox: false,
-        inline_css: false,
-        start_time: Some(Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap()),
-    };
+    let options = Options::new()
+        .open_tracking(true)
+        .click_tracking(true)
+        .start_time(Utc.with_ymd_and_hms(2019, 1, 1, 0, 0, 0).unwrap());
 
     // recipient with substitute data for the template
     let recipient = Recipient::with_substitution(
@@ -62,20 +57,13 @@ fn main() {
 
     match result {
         Ok(res) => {
-            println!("{:?}", &res);
-            match res {
-                TransmissionResponse::ApiResponse(api_res) => {
-                    println!("API Response: \n {:#?}", api_res);
-                    //   assert_eq!(1, api_res.total_accepted_recipients);
-                    //   assert_eq!(0, api_res.total_rejected_recipients);
-                }
-                TransmissionResponse::ApiError(errors) => {
-                    println!("Response Errors: \n {:#?}", &errors);
-                }
-            }
+            println!("API Response: \n {:#?}", res);
+            //   assert_eq!(1, res.total_accepted_recipients);
+            //   assert_eq!(0, res.total_rejected_recipients);
         }
         Err(error) => {
             println!("error \n {:#?}", error);
+            println!("Response Errors: \n {:#?}", error.api_errors());
         }
     }
 }
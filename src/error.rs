@@ -0,0 +1,337 @@
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::{fmt, time::Duration};
+
+use crate::transmission::{ParseAddressError, ValidationError};
+
+/// Error returned by the API as part of an `errors` response body
+///
+/// See <https://developers.sparkpost.com/api/#header-error-codes> for a list
+/// of codes.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct ApiError {
+    pub description: Option<String>,
+    pub code: Option<String>,
+    pub message: Option<String>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(code) = &self.code {
+            write!(f, "[{code}] ")?;
+        }
+
+        f.write_str(self.message.as_deref().unwrap_or("unknown error"))?;
+
+        if let Some(description) = &self.description {
+            write!(f, ": {description}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Errors that can occur when talking to the Sparkpost API
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The API responded with a non-success status code
+    ///
+    /// `errors` holds whatever error objects could be parsed from the body
+    /// and `retry_after` the delay requested by a `Retry-After` header.
+    Api {
+        status: StatusCode,
+        errors: Vec<ApiError>,
+        retry_after: Option<Duration>,
+    },
+
+    /// The request timed out
+    Timeout(reqwest::Error),
+
+    /// The request could not be sent or the response could not be read
+    Transport(reqwest::Error),
+
+    /// A request body could not be serialized or a response body could not
+    /// be deserialized
+    Serialization(serde_json::Error),
+
+    /// A value could not be used as an HTTP header, i.e. an API key
+    /// containing a newline
+    InvalidHeader(String),
+
+    /// The message was not sent because its content is invalid
+    InvalidMessage(String),
+
+    /// The message was not sent because [`Message::validate`] found
+    /// problems
+    ///
+    /// [`Message::validate`]: crate::transmission::Message::validate
+    Validation(Vec<ValidationError>),
+
+    /// An email address could not be parsed
+    InvalidAddress(ParseAddressError),
+
+    /// A file could not be read, i.e. an attachment
+    Io(std::io::Error),
+
+    /// The request failed after being retried, see
+    /// [`RetryPolicy`](crate::RetryPolicy)
+    ///
+    /// `attempts` counts all attempts including the first one and `last` is
+    /// the error of the final attempt.
+    Retried { attempts: u32, last: Box<Error> },
+}
+
+impl Error {
+    /// Wraps the error with the number of attempts made, if more than one.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn with_attempts(self, attempts: u32) -> Self {
+        if attempts > 1 {
+            Error::Retried {
+                attempts,
+                last: Box::new(self),
+            }
+        } else {
+            self
+        }
+    }
+
+    /// Error of the final attempt, unwrapping [`Error::Retried`]
+    fn last(&self) -> &Error {
+        match self {
+            Error::Retried { last, .. } => last.last(),
+            _ => self,
+        }
+    }
+
+    /// Number of attempts made before giving up
+    pub fn attempts(&self) -> u32 {
+        match self {
+            Error::Retried { attempts, .. } => *attempts,
+            _ => 1,
+        }
+    }
+
+    /// HTTP status code returned by the API, if a response was received
+    pub fn status(&self) -> Option<StatusCode> {
+        match self.last() {
+            Error::Api { status, .. } => Some(*status),
+            Error::Timeout(err) | Error::Transport(err) => err.status(),
+            _ => None,
+        }
+    }
+
+    /// Error objects returned by the API, empty for non-API errors
+    pub fn api_errors(&self) -> &[ApiError] {
+        match self.last() {
+            Error::Api { errors, .. } => errors,
+            _ => &[],
+        }
+    }
+
+    /// Returns true if the request timed out
+    pub fn is_timeout(&self) -> bool {
+        matches!(self.last(), Error::Timeout(_))
+    }
+
+    /// Delay requested by the API before retrying, if any
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self.last() {
+            Error::Api { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Returns true if sending the same request again may succeed
+    ///
+    /// This is the case for timeouts, connection failures, rate limiting
+    /// (`429 Too Many Requests`) and server errors (`5xx`).
+    pub fn is_retryable(&self) -> bool {
+        match self.last() {
+            Error::Api { status, .. } => {
+                *status == StatusCode::TOO_MANY_REQUESTS
+                    || status.is_server_error()
+            }
+            Error::Timeout(_) => true,
+            Error::Transport(err) => err.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if the requested resource does not exist
+    /// (`404 Not Found`)
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    /// Returns true if the request conflicts with the state of the resource
+    /// (`409 Conflict`), i.e. deleting a transmission that is already
+    /// generating
+    pub fn is_conflict(&self) -> bool {
+        self.status() == Some(StatusCode::CONFLICT)
+    }
+
+    /// Returns true if the API key was rejected or lacks the required
+    /// permissions
+    pub fn is_auth_error(&self) -> bool {
+        matches!(
+            self.status(),
+            Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        )
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api { status, errors, .. } => {
+                write!(f, "API responded with {status}")?;
+                for (idx, err) in errors.iter().enumerate() {
+                    f.write_str(if idx == 0 { ": " } else { "; " })?;
+                    write!(f, "{err}")?;
+                }
+                Ok(())
+            }
+            Error::Timeout(err) => write!(f, "request timed out: {err}"),
+            Error::Transport(err) => write!(f, "request failed: {err}"),
+            Error::Serialization(err) => {
+                write!(f, "JSON (de)serialization failed: {err}")
+            }
+            Error::InvalidHeader(name) => {
+                write!(f, "invalid value for header `{name}`")
+            }
+            Error::InvalidMessage(reason) => {
+                write!(f, "invalid message: {reason}")
+            }
+            Error::Validation(errors) => {
+                f.write_str("invalid message")?;
+                for (idx, err) in errors.iter().enumerate() {
+                    f.write_str(if idx == 0 { ": " } else { "; " })?;
+                    write!(f, "{err}")?;
+                }
+                Ok(())
+            }
+            Error::InvalidAddress(err) => write!(f, "{err}"),
+            Error::Io(err) => write!(f, "I/O error: {err}"),
+            Error::Retried { attempts, last } => {
+                write!(f, "{last} (after {attempts} attempts)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Timeout(err) | Error::Transport(err) => Some(err),
+            Error::Serialization(err) => Some(err),
+            Error::InvalidAddress(err) => Some(err),
+            Error::Io(err) => Some(err),
+            Error::Retried { last, .. } => Some(last.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            Error::Timeout(err)
+        } else {
+            Error::Transport(err)
+        }
+    }
+}
+
+impl From<ParseAddressError> for Error {
+    fn from(err: ParseAddressError) -> Self {
+        Error::InvalidAddress(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Serialization(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_error(status: StatusCode) -> Error {
+        Error::Api {
+            status,
+            errors: vec![ApiError {
+                description: Some(
+                    "Unconfigured or unverified sending domain.".to_owned(),
+                ),
+                code: Some("7001".to_owned()),
+                message: Some("Invalid domain".to_owned()),
+            }],
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn classify_api_errors() {
+        let err = api_error(StatusCode::TOO_MANY_REQUESTS);
+        assert!(err.is_retryable());
+        assert!(!err.is_auth_error());
+
+        let err = api_error(StatusCode::SERVICE_UNAVAILABLE);
+        assert!(err.is_retryable());
+
+        let err = api_error(StatusCode::UNAUTHORIZED);
+        assert!(!err.is_retryable());
+        assert!(err.is_auth_error());
+
+        let err = api_error(StatusCode::BAD_REQUEST);
+        assert!(!err.is_retryable());
+        assert!(!err.is_auth_error());
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(err.api_errors()[0].code.as_deref(), Some("7001"));
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    #[test]
+    fn retried() {
+        let err = api_error(StatusCode::SERVICE_UNAVAILABLE).with_attempts(1);
+        assert!(matches!(err, Error::Api { .. }));
+        assert_eq!(err.attempts(), 1);
+
+        let err = api_error(StatusCode::SERVICE_UNAVAILABLE).with_attempts(3);
+        assert_eq!(err.attempts(), 3);
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(err.is_retryable());
+        assert_eq!(err.api_errors().len(), 1);
+        assert!(err.to_string().ends_with("(after 3 attempts)"));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            api_error(StatusCode::BAD_REQUEST).to_string(),
+            "API responded with 400 Bad Request: [7001] Invalid domain: \
+             Unconfigured or unverified sending domain."
+        );
+        assert_eq!(
+            Error::InvalidHeader("Authorization".to_owned()).to_string(),
+            "invalid value for header `Authorization`"
+        );
+        assert_eq!(
+            Error::Validation(vec![
+                ValidationError::NoRecipients,
+                ValidationError::EmptySubject
+            ])
+            .to_string(),
+            "invalid message: no recipients; empty subject"
+        );
+    }
+}
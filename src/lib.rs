@@ -4,4 +4,16 @@
 //!
 //! [Sparkpost]: https://sparkpost.com
 
+mod error;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod rate_limit;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod retry;
+pub mod templates;
 pub mod transmission;
+
+pub use self::error::Error;
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use self::rate_limit::RateLimiter;
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use self::retry::RetryPolicy;
@@ -0,0 +1,164 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Client-side token bucket limiting the rate of API requests
+///
+/// The bucket holds up to `burst` tokens and is refilled at
+/// `requests_per_second`. Every request, including retries, takes one token;
+/// when the bucket is empty the blocking client sleeps and the async client
+/// awaits until a token becomes available.
+///
+/// Clones share the same bucket, so a single limiter can be handed to
+/// several clients to enforce an account-wide limit. Clients using a limiter
+/// can be shared across threads, i.e. wrapped in an `Arc`.
+///
+/// ```
+/// use sparklepost::{transmission::TransmissionBuilder, RateLimiter};
+///
+/// let limiter = RateLimiter::new(10.0, 20);
+///
+/// // both clients built from these share the same limit
+/// let global = TransmissionBuilder::new("api_key").rate_limiter(limiter.clone());
+/// let eu = TransmissionBuilder::new("api_key").eu().rate_limiter(limiter);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// available tokens, negative when callers are queued up
+    tokens: f64,
+    capacity: f64,
+    per_second: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// creates new limiter allowing `requests_per_second` on average and
+    /// bursts of up to `burst` requests
+    ///
+    /// The bucket starts full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests_per_second` is not a positive, finite number.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second.is_finite() && requests_per_second > 0.0,
+            "requests_per_second must be positive"
+        );
+
+        let capacity = f64::from(burst.max(1));
+
+        RateLimiter {
+            inner: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                capacity,
+                per_second: requests_per_second,
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Takes a token, returning how long the caller must wait before using
+    /// it.
+    pub(crate) fn reserve(&self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    fn reserve_at(&self, now: Instant) -> Duration {
+        let mut bucket = self
+            .inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let elapsed = now.saturating_duration_since(bucket.updated);
+        bucket.tokens = (bucket.tokens
+            + elapsed.as_secs_f64() * bucket.per_second)
+            .min(bucket.capacity);
+        bucket.updated = now;
+
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / bucket.per_second)
+        }
+    }
+
+    /// Blocks the current thread until a token is available.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Waits until a token is available.
+    #[cfg(feature = "async")]
+    pub(crate) async fn acquire_async(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_then_rate() {
+        let limiter = RateLimiter::new(10.0, 3);
+        let start = Instant::now();
+
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+
+        // queued callers wait successively longer
+        assert_eq!(limiter.reserve_at(start), Duration::from_millis(100));
+        assert_eq!(limiter.reserve_at(start), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn refill() {
+        let limiter = RateLimiter::new(10.0, 2);
+        let start = Instant::now();
+
+        limiter.reserve_at(start);
+        limiter.reserve_at(start);
+
+        let later = start + Duration::from_millis(100);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+
+        // refill never exceeds burst size
+        let much_later = later + Duration::from_secs(60);
+        assert_eq!(limiter.reserve_at(much_later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(much_later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(much_later), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn clones_share_bucket() {
+        let limiter = RateLimiter::new(1.0, 1);
+        let clone = limiter.clone();
+        let start = Instant::now();
+
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        assert_eq!(clone.reserve_at(start), Duration::from_secs(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_rate() {
+        RateLimiter::new(0.0, 1);
+    }
+}
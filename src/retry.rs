@@ -0,0 +1,268 @@
+use chrono::{DateTime, Utc};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    StatusCode,
+};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher as _, Hasher as _},
+    time::Duration,
+};
+
+use crate::Error;
+
+/// Retry policy for failed API requests
+///
+/// Requests are retried with exponential backoff when the API reports rate
+/// limiting (`429 Too Many Requests`), is temporarily unavailable or cannot be
+/// reached. A `Retry-After` header on the response takes precedence over the
+/// computed delay; if it asks to wait longer than [`max_delay`] the request is
+/// not retried.
+///
+/// Requests that are not idempotent, like sending a transmission, are only
+/// retried when the API certainly did not process them: rate limiting, `503
+/// Service Unavailable` and connection failures. Timeouts and other server
+/// errors are only retried for idempotent requests.
+///
+/// ```
+/// use std::time::Duration;
+/// use sparklepost::{transmission::TransmissionBuilder, RetryPolicy};
+///
+/// let builder = TransmissionBuilder::new("api_key").retry_policy(
+///     RetryPolicy::new()
+///         .max_attempts(5)
+///         .base_delay(Duration::from_millis(250))
+///         .max_delay(Duration::from_secs(10)),
+/// );
+/// ```
+///
+/// [`max_delay`]: RetryPolicy::max_delay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// creates new policy with 3 attempts, 500ms base delay, 30s max delay
+    /// and jitter enabled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// set maximum number of attempts, including the first one
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// set delay before the first retry, doubled for every further retry
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// set upper bound for the delay between attempts
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// enable or disable randomization of delays between attempts
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns the delay before the next attempt, or `None` if the failed
+    /// attempt should not be retried.
+    pub(crate) fn backoff(
+        &self,
+        attempts: u32,
+        idempotent: bool,
+        err: &Error,
+    ) -> Option<Duration> {
+        if attempts >= self.max_attempts || !should_retry(idempotent, err) {
+            return None;
+        }
+
+        if let Some(retry_after) = err.retry_after() {
+            return (retry_after <= self.max_delay).then_some(retry_after);
+        }
+
+        let delay = self
+            .base_delay
+            .checked_mul(1 << (attempts - 1).min(31))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+
+        if self.jitter {
+            // equal jitter: half fixed, half random
+            let half = delay / 2;
+            Some(half + half.mul_f64(random_fraction()))
+        } else {
+            Some(delay)
+        }
+    }
+}
+
+fn should_retry(idempotent: bool, err: &Error) -> bool {
+    if idempotent {
+        return err.is_retryable();
+    }
+
+    match err {
+        Error::Api { status, .. } => matches!(
+            *status,
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        ),
+        Error::Transport(err) => err.is_connect(),
+        _ => false,
+    }
+}
+
+/// Pseudo-random number in `0.0..1.0`, seeded by std's random hasher keys.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Parses a `Retry-After` header given either in seconds or as an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        date.with_timezone(&Utc)
+            .signed_duration_since(Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn api_error(status: StatusCode, retry_after: Option<Duration>) -> Error {
+        Error::Api {
+            status,
+            errors: Vec::new(),
+            retry_after,
+        }
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let policy = RetryPolicy::new()
+            .max_attempts(5)
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(300))
+            .jitter(false);
+        let err = api_error(StatusCode::TOO_MANY_REQUESTS, None);
+
+        assert_eq!(
+            policy.backoff(1, false, &err),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.backoff(2, false, &err),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            policy.backoff(3, false, &err),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(
+            policy.backoff(4, false, &err),
+            Some(Duration::from_millis(300))
+        );
+        assert_eq!(policy.backoff(5, false, &err), None);
+    }
+
+    #[test]
+    fn jitter_stays_in_bounds() {
+        let policy = RetryPolicy::new()
+            .max_attempts(10)
+            .base_delay(Duration::from_millis(100));
+        let err = api_error(StatusCode::TOO_MANY_REQUESTS, None);
+
+        for _ in 0..100 {
+            let delay = policy.backoff(2, false, &err).unwrap();
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn honors_retry_after() {
+        let policy = RetryPolicy::new().max_delay(Duration::from_secs(10));
+
+        let err = api_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(7)),
+        );
+        assert_eq!(
+            policy.backoff(1, false, &err),
+            Some(Duration::from_secs(7))
+        );
+
+        let err = api_error(
+            StatusCode::TOO_MANY_REQUESTS,
+            Some(Duration::from_secs(60)),
+        );
+        assert_eq!(policy.backoff(1, false, &err), None);
+    }
+
+    #[test]
+    fn non_idempotent_requests() {
+        let policy = RetryPolicy::new();
+
+        let err = api_error(StatusCode::INTERNAL_SERVER_ERROR, None);
+        assert!(policy.backoff(1, true, &err).is_some());
+        assert!(policy.backoff(1, false, &err).is_none());
+
+        let err = api_error(StatusCode::SERVICE_UNAVAILABLE, None);
+        assert!(policy.backoff(1, false, &err).is_some());
+
+        let err = api_error(StatusCode::BAD_REQUEST, None);
+        assert!(policy.backoff(1, true, &err).is_none());
+    }
+
+    #[test]
+    fn parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("soon"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
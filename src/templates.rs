@@ -0,0 +1,398 @@
+//! Preview stored templates and inline content as rendered by the API
+//!
+//! Template clients borrow the configuration of a transmission client,
+//! including its retry policy and rate limiter.
+//!
+//! ```no_run
+//! # #[cfg(feature = "blocking")] {
+//! use serde_json::json;
+//! use sparklepost::transmission::Transmission;
+//!
+//! let tm = Transmission::new("api_key");
+//! let preview = tm
+//!     .templates()
+//!     .preview("christmas_offer", json!({ "first_name": "Wilma" }), true)
+//!     .unwrap();
+//!
+//! println!("{}", preview.subject);
+//! # }
+//! ```
+//!
+//! For rendering without calling the API see
+//! [`Message::render`](crate::transmission::Message::render).
+
+use serde::Deserialize;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde::Serialize;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::{to_value, Value};
+use std::collections::BTreeMap;
+
+#[cfg(feature = "async")]
+use crate::transmission::AsyncTransmission;
+use crate::transmission::EmailAddress;
+#[cfg(feature = "blocking")]
+use crate::transmission::Transmission;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::{
+    transmission::{construct_headers, encode_path_segment, Content, Message},
+    Error,
+};
+
+/// Content of a template or message as rendered by the API
+///
+/// For more info see <https://developers.sparkpost.com/api/templates/#templates-post-preview-a-template>.
+#[derive(Debug, Deserialize, PartialEq)]
+pub struct TemplatePreview {
+    pub from: EmailAddress,
+    pub subject: String,
+    pub reply_to: Option<String>,
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub amp_html: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+#[derive(Debug, Serialize)]
+struct PreviewRequest<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<&'a Content>,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    substitution_data: Value,
+}
+
+/// Returns the endpoint previewing a stored template.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn template_preview_url(base_url: &str, template_id: &str) -> String {
+    format!(
+        "{base_url}/templates/{}/preview",
+        encode_path_segment(template_id)
+    )
+}
+
+/// Returns the endpoint, `draft` query parameter and request body previewing
+/// `message`. Only stored templates have a draft version.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn message_preview<'a>(
+    base_url: &str,
+    message: &'a Message,
+) -> (String, Option<bool>, PreviewRequest<'a>) {
+    let substitution_data =
+        message.substitution_data.clone().unwrap_or_default();
+
+    match message.content {
+        Content::Template {
+            ref template_id,
+            use_draft_template,
+        } => (
+            template_preview_url(base_url, template_id),
+            Some(use_draft_template),
+            PreviewRequest {
+                content: None,
+                substitution_data,
+            },
+        ),
+        ref content => (
+            format!("{base_url}/utils/content-previewer"),
+            None,
+            PreviewRequest {
+                content: Some(content),
+                substitution_data,
+            },
+        ),
+    }
+}
+
+/// Sparkpost Templates, see [`Transmission::templates`]
+#[cfg(feature = "blocking")]
+#[derive(Debug)]
+pub struct Templates<'a> {
+    tm: &'a Transmission,
+}
+
+#[cfg(feature = "blocking")]
+impl Transmission {
+    /// access the templates API with the configuration of this client
+    pub fn templates(&self) -> Templates<'_> {
+        Templates { tm: self }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl Templates<'_> {
+    /// Render a stored template with `substitution_data`, using the draft
+    /// instead of the published version if `draft` is set
+    pub fn preview<T: Serialize>(
+        &self,
+        template_id: &str,
+        substitution_data: T,
+        draft: bool,
+    ) -> Result<TemplatePreview, Error> {
+        let url = template_preview_url(&self.tm.base_url, template_id);
+        let body = PreviewRequest {
+            content: None,
+            substitution_data: to_value(substitution_data)?,
+        };
+
+        self.send(url, Some(draft), &body)
+    }
+
+    /// Render the content of `message` with its substitution data
+    ///
+    /// Inline and RFC 822 content is rendered by the content previewer,
+    /// stored templates are previewed like with [`preview`].
+    ///
+    /// [`preview`]: Templates::preview
+    pub fn preview_message(
+        &self,
+        message: &Message,
+    ) -> Result<TemplatePreview, Error> {
+        let (url, draft, body) = message_preview(&self.tm.base_url, message);
+        self.send(url, draft, &body)
+    }
+
+    fn send(
+        &self,
+        url: String,
+        draft: Option<bool>,
+        body: &PreviewRequest<'_>,
+    ) -> Result<TemplatePreview, Error> {
+        let mut req = self
+            .tm
+            .client
+            .post(url)
+            .headers(construct_headers(&self.tm.api_key)?)
+            .body(serde_json::to_vec(body)?);
+
+        if let Some(draft) = draft {
+            req = req.query(&[("draft", draft)]);
+        }
+
+        // previews have no side effects
+        self.tm.execute(req, true)
+    }
+}
+
+/// Sparkpost Templates, see [`AsyncTransmission::templates`]
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncTemplates<'a> {
+    tm: &'a AsyncTransmission,
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransmission {
+    /// access the templates API with the configuration of this client
+    pub fn templates(&self) -> AsyncTemplates<'_> {
+        AsyncTemplates { tm: self }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTemplates<'_> {
+    /// Render a stored template with `substitution_data`, using the draft
+    /// instead of the published version if `draft` is set
+    pub async fn preview<T: Serialize>(
+        &self,
+        template_id: &str,
+        substitution_data: T,
+        draft: bool,
+    ) -> Result<TemplatePreview, Error> {
+        let url = template_preview_url(&self.tm.base_url, template_id);
+        let body = PreviewRequest {
+            content: None,
+            substitution_data: to_value(substitution_data)?,
+        };
+
+        self.send(url, Some(draft), &body).await
+    }
+
+    /// Render the content of `message` with its substitution data, see
+    /// [`Templates::preview_message`]
+    pub async fn preview_message(
+        &self,
+        message: &Message,
+    ) -> Result<TemplatePreview, Error> {
+        let (url, draft, body) = message_preview(&self.tm.base_url, message);
+        self.send(url, draft, &body).await
+    }
+
+    async fn send(
+        &self,
+        url: String,
+        draft: Option<bool>,
+        body: &PreviewRequest<'_>,
+    ) -> Result<TemplatePreview, Error> {
+        let mut req = self
+            .tm
+            .client
+            .post(url)
+            .headers(construct_headers(&self.tm.api_key)?)
+            .body(serde_json::to_vec(body)?);
+
+        if let Some(draft) = draft {
+            req = req.query(&[("draft", draft)]);
+        }
+
+        // previews have no side effects
+        self.tm.execute(req, true).await
+    }
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
+mod tests {
+    use super::*;
+    use mockito::Matcher;
+    use serde_json::json;
+
+    const PREVIEW: &str = r#"{
+        "results": {
+            "from": { "email": "marketing@example.com", "name": "Example" },
+            "subject": "Hello Wilma",
+            "reply_to": "support@example.com",
+            "text": "Hi Wilma",
+            "html": "<p>Hi Wilma</p>",
+            "headers": { "X-Campaign": "sale" }
+        }
+    }"#;
+
+    fn expected() -> TemplatePreview {
+        TemplatePreview {
+            from: EmailAddress::new("marketing@example.com", "Example"),
+            subject: "Hello Wilma".to_owned(),
+            reply_to: Some("support@example.com".to_owned()),
+            text: Some("Hi Wilma".to_owned()),
+            html: Some("<p>Hi Wilma</p>".to_owned()),
+            amp_html: None,
+            headers: [("X-Campaign".to_owned(), "sale".to_owned())].into(),
+        }
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn preview_template() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/templates/offer/preview")
+            .match_query(Matcher::UrlEncoded("draft".into(), "true".into()))
+            .match_header("authorization", "key")
+            .match_body(Matcher::Json(json!({
+                "substitution_data": { "name": "Wilma" }
+            })))
+            .with_body(PREVIEW)
+            .create();
+
+        let tm = Transmission::builder("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        let preview = tm
+            .templates()
+            .preview("offer", json!({ "name": "Wilma" }), true)
+            .unwrap();
+
+        assert_eq!(preview, expected());
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn preview_message() {
+        let mut server = mockito::Server::new();
+        let inline = server
+            .mock("POST", "/utils/content-previewer")
+            .match_query(Matcher::Exact(String::new()))
+            .match_body(Matcher::Json(json!({
+                "content": {
+                    "from": { "email": "marketing@example.com", "name": null },
+                    "subject": "Hello {{name}}",
+                    "text": "Hi {{name}}"
+                },
+                "substitution_data": { "name": "Wilma" }
+            })))
+            .with_body(PREVIEW)
+            .create();
+        let template = server
+            .mock("POST", "/templates/offer/preview")
+            .match_query(Matcher::UrlEncoded("draft".into(), "false".into()))
+            .match_body(Matcher::Json(json!({})))
+            .with_body(PREVIEW)
+            .create();
+
+        let tm = Transmission::builder("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        let mut email = Message::new("marketing@example.com");
+        email
+            .subject("Hello {{name}}")
+            .text("Hi {{name}}")
+            .substitution_data(json!({ "name": "Wilma" }));
+
+        assert_eq!(tm.templates().preview_message(&email).unwrap(), expected());
+
+        let mut email = Message::default();
+        email.template_id("offer");
+        tm.templates().preview_message(&email).unwrap();
+
+        inline.assert();
+        template.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn encoded_template_id() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/templates/spring%20sale%2F2%3F/preview")
+            .match_query(Matcher::UrlEncoded("draft".into(), "false".into()))
+            .with_body(PREVIEW)
+            .expect(2)
+            .create();
+
+        let tm = Transmission::builder("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        tm.templates()
+            .preview("spring sale/2?", json!({}), false)
+            .unwrap();
+
+        let mut email = Message::default();
+        email.template_id("spring sale/2?");
+        tm.templates().preview_message(&email).unwrap();
+
+        mock.assert();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_preview_template() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/templates/offer/preview")
+            .match_query(Matcher::UrlEncoded("draft".into(), "false".into()))
+            .with_body(PREVIEW)
+            .create_async()
+            .await;
+
+        let tm = AsyncTransmission::builder("key")
+            .base_url(server.url())
+            .build_async()
+            .unwrap();
+
+        let preview = tm
+            .templates()
+            .preview("offer", json!({ "name": "Wilma" }), false)
+            .await
+            .unwrap();
+
+        assert_eq!(preview, expected());
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,402 @@
+use reqwest::Proxy;
+use std::time::Duration;
+
+#[cfg(feature = "async")]
+use super::AsyncTransmission;
+#[cfg(feature = "blocking")]
+use super::Transmission;
+use super::{EU_BASE_URL, GLOBAL_BASE_URL};
+use crate::{Error, RateLimiter, RetryPolicy};
+
+const USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// Builder for [`Transmission`] and [`AsyncTransmission`] clients
+///
+/// ```
+/// use std::time::Duration;
+/// use sparklepost::transmission::TransmissionBuilder;
+///
+/// let builder = TransmissionBuilder::new("api_key")
+///     .base_url("https://example.api.e.sparkpost.com/api/v1")
+///     .timeout(Duration::from_secs(30))
+///     .connect_timeout(Duration::from_secs(5))
+///     .user_agent("my-app/1.0");
+///
+/// # #[cfg(feature = "blocking")]
+/// let tm = builder.build().unwrap();
+/// ```
+///
+/// When a pre-configured HTTP client is supplied with [`client`] or
+/// [`async_client`], the timeout, proxy and user agent settings are ignored
+/// for that client.
+///
+/// [`client`]: TransmissionBuilder::client
+/// [`async_client`]: TransmissionBuilder::async_client
+#[derive(Debug)]
+pub struct TransmissionBuilder {
+    api_key: String,
+    base_url: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxy: Option<Proxy>,
+    user_agent: String,
+    retry_policy: Option<RetryPolicy>,
+    rate_limiter: Option<RateLimiter>,
+    validate: bool,
+    #[cfg(feature = "blocking")]
+    client: Option<reqwest::blocking::Client>,
+    #[cfg(feature = "async")]
+    async_client: Option<reqwest::Client>,
+}
+
+impl TransmissionBuilder {
+    /// creates new builder with api key, targeting the global API
+    pub fn new<T: Into<String>>(api_key: T) -> Self {
+        TransmissionBuilder {
+            api_key: api_key.into(),
+            base_url: GLOBAL_BASE_URL.to_owned(),
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            user_agent: USER_AGENT.to_owned(),
+            retry_policy: None,
+            rate_limiter: None,
+            validate: false,
+            #[cfg(feature = "blocking")]
+            client: None,
+            #[cfg(feature = "async")]
+            async_client: None,
+        }
+    }
+
+    /// target the EU version of the API
+    pub fn eu(self) -> Self {
+        self.base_url(EU_BASE_URL)
+    }
+
+    /// set API base URL, i.e. `https://api.sparkpost.com/api/v1`
+    ///
+    /// Useful for SparkPost Enterprise hosts or a local stand-in server.
+    pub fn base_url<T: Into<String>>(mut self, base_url: T) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_owned();
+        self
+    }
+
+    /// set total timeout for each request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// set timeout for establishing connections
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// route requests through a proxy
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// set `User-Agent` header, defaults to `sparklepost/<version>`
+    pub fn user_agent<T: Into<String>>(mut self, user_agent: T) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// retry failed requests according to policy, disabled by default
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// limit the rate of requests, disabled by default
+    pub fn rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// validate messages with [`Message::validate`] before sending, disabled
+    /// by default
+    ///
+    /// Invalid messages are not sent and reported as [`Error::Validation`].
+    /// Messages with inline fields or CC recipients set on other content, AMP
+    /// HTML without HTML fallback or line breaks in header values are never
+    /// sent.
+    ///
+    /// [`Message::validate`]: super::Message::validate
+    pub fn validate(mut self, validate: bool) -> Self {
+        self.validate = validate;
+        self
+    }
+
+    /// use a pre-configured blocking HTTP client
+    #[cfg(feature = "blocking")]
+    pub fn client(mut self, client: reqwest::blocking::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// use a pre-configured async HTTP client
+    #[cfg(feature = "async")]
+    pub fn async_client(mut self, client: reqwest::Client) -> Self {
+        self.async_client = Some(client);
+        self
+    }
+
+    /// build a blocking [`Transmission`]
+    #[cfg(feature = "blocking")]
+    pub fn build(self) -> Result<Transmission, Error> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = reqwest::blocking::Client::builder()
+                    .user_agent(self.user_agent);
+
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+
+                builder.build()?
+            }
+        };
+
+        Ok(Transmission {
+            api_key: self.api_key,
+            url: format!("{}/transmissions", self.base_url),
+            base_url: self.base_url,
+            client,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+            validate: self.validate,
+        })
+    }
+
+    /// build an [`AsyncTransmission`]
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<AsyncTransmission, Error> {
+        let client = match self.async_client {
+            Some(client) => client,
+            None => {
+                let mut builder =
+                    reqwest::Client::builder().user_agent(self.user_agent);
+
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+
+                if let Some(proxy) = self.proxy {
+                    builder = builder.proxy(proxy);
+                }
+
+                builder.build()?
+            }
+        };
+
+        Ok(AsyncTransmission {
+            api_key: self.api_key,
+            url: format!("{}/transmissions", self.base_url),
+            base_url: self.base_url,
+            client,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+            validate: self.validate,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transmission::testing::{message, SEND_RESULTS};
+
+    #[test]
+    fn base_url() {
+        let builder = TransmissionBuilder::new("key");
+        assert_eq!(builder.base_url, GLOBAL_BASE_URL);
+
+        let builder = builder.eu();
+        assert_eq!(builder.base_url, EU_BASE_URL);
+
+        let builder = builder.base_url("http://localhost:1234/api/v1/");
+        assert_eq!(builder.base_url, "http://localhost:1234/api/v1");
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn send_to_custom_base_url() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/api/v1/transmissions")
+            .match_header("authorization", "key")
+            .match_header("user-agent", USER_AGENT)
+            .with_status(200)
+            .with_body(SEND_RESULTS)
+            .create();
+
+        let tm = TransmissionBuilder::new("key")
+            .base_url(format!("{}/api/v1", server.url()))
+            .build()
+            .unwrap();
+
+        let res = tm.send(&message()).unwrap();
+        assert_eq!(res.id, "11668787484950529");
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn custom_user_agent() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/transmissions")
+            .match_header("user-agent", "my-app/1.0")
+            .with_body(SEND_RESULTS)
+            .create();
+
+        let tm = TransmissionBuilder::new("key")
+            .base_url(server.url())
+            .user_agent("my-app/1.0")
+            .build()
+            .unwrap();
+
+        tm.send(&message()).unwrap();
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn injected_client() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/transmissions")
+            .match_header("user-agent", "injected")
+            .with_body(SEND_RESULTS)
+            .create();
+
+        let client = reqwest::blocking::Client::builder()
+            .user_agent("injected")
+            .build()
+            .unwrap();
+
+        let tm = TransmissionBuilder::new("key")
+            .base_url(server.url())
+            .user_agent("ignored")
+            .client(client)
+            .build()
+            .unwrap();
+
+        tm.send(&message()).unwrap();
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn validation() {
+        use crate::transmission::ValidationError;
+
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/transmissions")
+            .with_body(SEND_RESULTS)
+            .expect(1)
+            .create();
+
+        let mut email = message();
+        email.subject("");
+
+        let tm = TransmissionBuilder::new("key")
+            .base_url(server.url())
+            .validate(true)
+            .build()
+            .unwrap();
+
+        let err = tm.send(&email).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Validation(ref errors)
+                if errors[..] == [ValidationError::EmptySubject]
+        ));
+
+        let tm = TransmissionBuilder::new("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        // content that would break the email is rejected regardless
+        let mut amp = message();
+        amp.amp_html("<html ⚡4email></html>");
+        let err = tm.send(&amp).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Validation(ref errors)
+                if errors[..] == [ValidationError::MissingHtmlFallback]
+        ));
+
+        tm.send(&email).unwrap();
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn timeout() {
+        use std::{io::Read as _, net::TcpListener, thread};
+
+        // accepts connections but never responds
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let _ = stream.read(&mut [0; 1024]);
+                thread::sleep(Duration::from_secs(5));
+            }
+        });
+
+        let tm = TransmissionBuilder::new("key")
+            .base_url(format!("http://{addr}"))
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let err = tm.send(&message()).unwrap_err();
+        assert!(err.is_timeout());
+        assert!(err.is_retryable());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn async_send_to_custom_base_url() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/api/v1/transmissions")
+            .match_header("authorization", "key")
+            .match_header("user-agent", USER_AGENT)
+            .with_body(SEND_RESULTS)
+            .create_async()
+            .await;
+
+        let tm = TransmissionBuilder::new("key")
+            .base_url(format!("{}/api/v1", server.url()))
+            .build_async()
+            .unwrap();
+
+        let res = tm.send(&message()).await.unwrap();
+        assert_eq!(res.total_accepted_recipients, 1);
+        mock.assert_async().await;
+    }
+}
@@ -1,15 +1,25 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use chrono::{DateTime, Utc};
-use serde::Serialize;
-use serde_json::{to_value, Value};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{to_value, Map, Value};
+use std::{collections::BTreeMap, fs, path::Path};
 
-use super::models::{EmailAddress, Recipient, RecipientSet};
+use super::models::{
+    EmailAddress, MergedRecipient, Normalization, Recipient, RecipientKind,
+    RecipientSet,
+};
+use crate::Error;
+
+/// Maximum size of a transmission accepted by SparkPost, including
+/// base64-encoded attachments
+pub const MAX_MESSAGE_SIZE: usize = 20 * 1024 * 1024;
 
 /// Represents email message including some mata-data
 ///
 /// # Examples
 ///
 /// ```
-/// use sparkpost::transmission::{Message, EmailAddress};
+/// use sparklepost::transmission::{Message, EmailAddress};
 ///
 /// let mut email = Message::new(
 ///     EmailAddress::new("marketing@example.sink.sparkpostmail.com", "Example Company")
@@ -47,23 +57,34 @@ use super::models::{EmailAddress, Recipient, RecipientSet};
 ///   }
 /// }
 /// ```
-#[derive(Debug, Default, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(from = "MessageData")]
 pub struct Message {
     pub options: Options,
     pub description: Option<String>,
     pub campaign_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_path: Option<String>,
     pub metadata: Option<Value>,
     pub substitution_data: Option<Value>,
     pub recipients: RecipientSet,
     pub(crate) content: Content,
+    #[serde(skip)]
+    normalization: Normalization,
+    #[serde(skip)]
+    merged: Vec<MergedRecipient>,
+    /// inline fields set on stored template or RFC 822 content
+    #[serde(skip)]
+    ignored_fields: Vec<&'static str>,
 }
 
 impl Message {
     /// create new message with sender emailAddress
     pub fn new<T: Into<EmailAddress>>(sender_address: T) -> Self {
-        let mut message = Message::default();
-        message.content.from = sender_address.into();
-        message
+        Message {
+            content: Content::Inline(InlineContent::new(sender_address.into())),
+            ..Default::default()
+        }
     }
 
     /// create new message with sending options
@@ -71,13 +92,39 @@ impl Message {
         sender_address: EmailAddress,
         options: Options,
     ) -> Self {
-        let mut message = Message {
+        Message {
             options,
-            ..Default::default()
-        };
+            ..Message::new(sender_address)
+        }
+    }
 
-        message.content.from = sender_address;
-        message
+    /// create new message from a pre-built RFC 822 message
+    ///
+    /// Sender, subject, body and headers are taken from the raw MIME
+    /// message; setting any of them on the `Message` has no effect and is
+    /// reported by [`validate`](Message::validate). Recipients, options and
+    /// substitution data are used as usual.
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::Message;
+    ///
+    /// let mut email = Message::from_rfc822(
+    ///     "Content-Type: text/plain\r\n\
+    ///      To: \"{{address.name}}\" <{{address.email}}>\r\n\
+    ///      From: marketing@example.sink.sparkpostmail.com\r\n\
+    ///      Subject: Hello\r\n\
+    ///      \r\n\
+    ///      Hello, {{address.name}}!\r\n",
+    /// );
+    /// email.add_recipient("wilma@example.sink.sparkpostmail.com");
+    /// ```
+    pub fn from_rfc822<T: Into<String>>(email_rfc822: T) -> Self {
+        Message {
+            content: Content::Rfc822 {
+                email_rfc822: email_rfc822.into(),
+            },
+            ..Default::default()
+        }
     }
 
     /// set an recipient list stored in the api
@@ -86,37 +133,209 @@ impl Message {
     /// see [Transport API ref](https://developers.sparkpost.com/api/transmissions/#header-stored-recipient-list)
     pub fn recipient_list(&mut self, list_name: &str) -> &mut Self {
         self.recipients = RecipientSet::ListName(list_name.into());
+        self.merged.clear();
+        self.update_copy_headers();
+        self
+    }
+
+    /// set how recipient addresses are compared when de-duplicating
+    ///
+    /// Recipients already added are de-duplicated again, keeping the last
+    /// one added. Domains are compared case-insensitively by default.
+    pub fn normalization(&mut self, normalization: Normalization) -> &mut Self {
+        self.normalization = normalization;
+
+        if let RecipientSet::LocalList(ref mut list) = self.recipients {
+            let mut kept: Vec<Recipient> = Vec::with_capacity(list.len());
+
+            for recipient in list.drain(..) {
+                merge_into(
+                    &mut kept,
+                    recipient,
+                    normalization,
+                    &mut self.merged,
+                );
+            }
+
+            *list = kept;
+        }
+
+        self.update_copy_headers();
         self
     }
 
+    /// recipients that were replaced by a later one with the same normalized
+    /// address, see [`normalization`](Message::normalization)
+    ///
+    /// Cleared when a [`recipient_list`](Message::recipient_list) replaces
+    /// the recipients.
+    pub fn merged_recipients(&self) -> &[MergedRecipient] {
+        &self.merged
+    }
+
     /// add an address to recipient list
     ///
-    /// Recipient is replaced if they have same email address
+    /// Recipient is replaced if they have same normalized email address, see
+    /// [`normalization`](Message::normalization).
     pub fn add_recipient<T: Into<Recipient>>(
         &mut self,
         recipient: T,
     ) -> &mut Self {
-        let recipient: Recipient = recipient.into();
+        self.push_recipient(recipient.into(), RecipientKind::To)
+    }
+
+    /// add a carbon copy recipient
+    ///
+    /// The recipient is shown in the `CC` header and sees the "To" recipients
+    /// in their `To` header. Recipient is replaced if they have same
+    /// normalized email address.
+    ///
+    /// Only inline content gets a `CC` header, messages with CC recipients and
+    /// stored template or RFC 822 content are not sent. Use
+    /// [`add_bcc`](Message::add_bcc) and list the recipients in the `CC`
+    /// header of the template or MIME message instead.
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::Message;
+    ///
+    /// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
+    /// email
+    ///     .add_recipient("wilma@example.sink.sparkpostmail.com")
+    ///     .add_cc("fred@example.sink.sparkpostmail.com")
+    ///     .add_bcc("barney@example.sink.sparkpostmail.com");
+    /// ```
+    pub fn add_cc<T: Into<Recipient>>(&mut self, recipient: T) -> &mut Self {
+        self.push_recipient(recipient.into(), RecipientKind::Cc)
+    }
+
+    /// add a blind carbon copy recipient
+    ///
+    /// The recipient is not shown to anyone else and sees the "To"
+    /// recipients in their `To` header. Recipient is replaced if they have
+    /// same normalized email address.
+    pub fn add_bcc<T: Into<Recipient>>(&mut self, recipient: T) -> &mut Self {
+        self.push_recipient(recipient.into(), RecipientKind::Bcc)
+    }
+
+    fn push_recipient(
+        &mut self,
+        mut recipient: Recipient,
+        kind: RecipientKind,
+    ) -> &mut Self {
+        recipient.kind = kind;
+
         match self.recipients {
             RecipientSet::ListName(_) => {
-                self.recipients = RecipientSet::LocalList(vec![recipient])
+                self.recipients = RecipientSet::LocalList(vec![recipient]);
+                self.merged.clear();
+            }
+            RecipientSet::LocalList(ref mut list) => merge_into(
+                list,
+                recipient,
+                self.normalization,
+                &mut self.merged,
+            ),
+        }
+
+        self.update_copy_headers();
+        self
+    }
+
+    /// Sets `header_to` of CC and BCC recipients to the "To" recipients and
+    /// lists the CC recipients in the `CC` header of inline content.
+    ///
+    /// `header_to` is left unset while there are no "To" recipients, which
+    /// [`validate`](Message::validate) reports.
+    fn update_copy_headers(&mut self) {
+        let list = match self.recipients {
+            RecipientSet::LocalList(ref mut list) => list,
+            RecipientSet::ListName(_) => {
+                if let Content::Inline(ref mut content) = self.content {
+                    content.headers.remove("CC");
+                }
+                return;
+            }
+        };
+
+        let join = |list: &[Recipient], kind: RecipientKind| {
+            list.iter()
+                .filter(|rec| rec.kind == kind)
+                .map(|rec| rec.address.email.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let to = join(list, RecipientKind::To);
+        let cc = join(list, RecipientKind::Cc);
+
+        for rec in list.iter_mut() {
+            rec.address.header_to = match rec.kind {
+                RecipientKind::To => None,
+                RecipientKind::Cc | RecipientKind::Bcc if to.is_empty() => None,
+                RecipientKind::Cc | RecipientKind::Bcc => Some(to.clone()),
+            };
+        }
+
+        if let Content::Inline(ref mut content) = self.content {
+            if cc.is_empty() {
+                content.headers.remove("CC");
+            } else {
+                content.headers.insert("CC".to_owned(), cc);
             }
-            RecipientSet::LocalList(ref mut list) => {
-                list.retain(|rec| {
-                    rec.address.email.as_str()
-                        != recipient.address.email.as_str()
-                });
-                list.push(recipient);
+        }
+    }
+
+    /// Sets a field of inline content. Stored template and RFC 822 content
+    /// is kept as is and `field` is reported by
+    /// [`validate`](Message::validate) instead.
+    fn set_inline<F>(&mut self, field: &'static str, set: F) -> &mut Self
+    where
+        F: FnOnce(&mut InlineContent),
+    {
+        match self.content {
+            Content::Inline(ref mut content) => set(content),
+            Content::Template { .. } | Content::Rfc822 { .. } => {
+                if !self.ignored_fields.contains(&field) {
+                    self.ignored_fields.push(field);
+                }
             }
         }
 
         self
     }
 
+    /// inline fields that were set on stored template or RFC 822 content
+    pub(crate) fn ignored_fields(&self) -> &[&'static str] {
+        &self.ignored_fields
+    }
+
+    /// set message content, replacing any previous content and sender
+    ///
+    /// Inline content is built with setters like [`subject`](Message::subject)
+    /// instead, [`Content::Inline`] is only available from deserialized
+    /// content, i.e. to resend a retrieved transmission.
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::{Content, Message};
+    ///
+    /// let mut email = Message::default();
+    /// email
+    ///     .add_recipient("wilma@example.sink.sparkpostmail.com")
+    ///     .content(Content::Template {
+    ///         template_id: "christmas_offer".to_owned(),
+    ///         use_draft_template: true,
+    ///     });
+    /// ```
+    pub fn content(&mut self, content: Content) -> &mut Self {
+        self.content = content;
+        self.ignored_fields.clear();
+        self.update_copy_headers();
+        self
+    }
+
     /// set message subject
     pub fn subject<T: Into<String>>(&mut self, subject: T) -> &mut Self {
-        self.content.subject = subject.into();
-        self
+        self.set_inline("subject", |content| content.subject = subject.into())
     }
     /// set message options
     pub fn options<T: Into<Options>>(&mut self, options: T) -> &mut Self {
@@ -125,12 +344,30 @@ impl Message {
     }
     /// set content html
     pub fn html<T: Into<String>>(&mut self, html: T) -> &mut Self {
-        self.content.html = Some(html.into());
-        self
+        self.set_inline("html", |content| content.html = Some(html.into()))
     }
     /// set content text
     pub fn text<T: Into<String>>(&mut self, text: T) -> &mut Self {
-        self.content.text = Some(text.into());
+        self.set_inline("text", |content| content.text = Some(text.into()))
+    }
+    /// set content AMP HTML
+    ///
+    /// Mail clients without AMP support display the HTML body instead, so
+    /// [`validate`](Message::validate) fails unless [`html`](Message::html)
+    /// is set as well.
+    pub fn amp_html<T: Into<String>>(&mut self, amp_html: T) -> &mut Self {
+        self.set_inline("amp_html", |content| {
+            content.amp_html = Some(amp_html.into())
+        })
+    }
+    /// set bounce address, used as envelope sender
+    ///
+    /// The domain must be a verified bounce domain.
+    pub fn return_path<T: Into<String>>(
+        &mut self,
+        return_path: T,
+    ) -> &mut Self {
+        self.return_path = Some(return_path.into());
         self
     }
     /// set campaign id
@@ -141,31 +378,58 @@ impl Message {
         self.campaign_id = Some(campaign_id.into());
         self
     }
-    /// set template id for content
+    /// use the published version of a stored template as content
+    ///
+    /// Replaces inline or RFC 822 content, sender and subject are taken from
+    /// the template. Use [`content`](Message::content) to send the draft
+    /// version instead.
     pub fn template_id<T: Into<String>>(
         &mut self,
         template_id: T,
     ) -> &mut Self {
-        self.content.template_id = Some(template_id.into());
-        self
+        self.content(Content::Template {
+            template_id: template_id.into(),
+            use_draft_template: false,
+        })
     }
 
-    /// set substitution_dat
+    /// set substitution_data, panics where [`try_substitution_data`] fails
+    ///
+    /// [`try_substitution_data`]: Message::try_substitution_data
     pub fn substitution_data<T: Serialize>(&mut self, data: T) -> &mut Self {
-        self.substitution_data =
-            Some(to_value(data).expect("Data cannot be searized"));
-        self
+        self.try_substitution_data(data)
+            .expect("Data cannot be serialized")
     }
 
-    /// set metadata
+    /// set substitution_data, failing if `data` cannot be serialized to JSON,
+    /// i.e. a map with non-string keys
+    pub fn try_substitution_data<T: Serialize>(
+        &mut self,
+        data: T,
+    ) -> Result<&mut Self, Error> {
+        self.substitution_data = Some(to_value(data)?);
+        Ok(self)
+    }
+
+    /// set metadata, panics where [`try_metadata`] fails
+    ///
+    /// [`try_metadata`]: Message::try_metadata
     pub fn metadata<T: Serialize>(&mut self, data: T) -> &mut Self {
-        self.metadata = Some(to_value(data).expect("Data cannot be searized"));
-        self
+        self.try_metadata(data).expect("Data cannot be serialized")
+    }
+
+    /// set metadata, failing if `data` cannot be serialized to JSON
+    pub fn try_metadata<T: Serialize>(
+        &mut self,
+        data: T,
+    ) -> Result<&mut Self, Error> {
+        self.metadata = Some(to_value(data)?);
+        Ok(self)
     }
 
     /// adds attachment to Message, multiple attachments allowed
     /// ``` rust
-    /// use sparkpost::transmission::{Message, Attachment};
+    /// use sparklepost::transmission::{Message, Attachment};
     ///
     /// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
     /// let attachment = Attachment::from_data(
@@ -179,48 +443,230 @@ impl Message {
         &mut self,
         attachment: T,
     ) -> &mut Self {
-        self.content.attachments.push(attachment.into());
-        self
+        self.set_inline("attachments", |content| {
+            content.attachments.push(attachment.into())
+        })
+    }
+
+    /// adds image to Message that is embedded in the HTML body, multiple
+    /// images allowed
+    ///
+    /// The HTML body must reference the image as `cid:<name>`, otherwise
+    /// [`validate`](Message::validate) fails.
+    pub fn add_inline_image<T: Into<InlineImage>>(
+        &mut self,
+        image: T,
+    ) -> &mut Self {
+        self.set_inline("inline_images", |content| {
+            content.inline_images.push(image.into())
+        })
+    }
+
+    /// set `Reply-To` address
+    pub fn reply_to<T: Into<String>>(&mut self, reply_to: T) -> &mut Self {
+        self.set_inline("reply_to", |content| {
+            content.reply_to = Some(reply_to.into())
+        })
+    }
+
+    /// set a custom email header, replacing any previous value
+    ///
+    /// The `CC` header is managed by [`add_cc`](Message::add_cc) and will be
+    /// overwritten when recipients change. Messages with line breaks in a
+    /// header value are not sent.
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::Message;
+    ///
+    /// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
+    /// email.header("X-Customer-Campaign-ID", "big_sale");
+    /// ```
+    pub fn header<N: Into<String>, V: Into<String>>(
+        &mut self,
+        name: N,
+        value: V,
+    ) -> &mut Self {
+        self.set_inline("headers", |content| {
+            content.headers.insert(name.into(), value.into());
+        })
+    }
+
+    /// set `List-Unsubscribe` headers
+    ///
+    /// `url` is the unsubscribe link and `mailto` an optional address
+    /// receiving unsubscribe requests by email. When `url` is an HTTPS link,
+    /// `List-Unsubscribe-Post` is set as well to enable one-click
+    /// unsubscribing ([RFC 8058]), which is required for bulk senders by some
+    /// mailbox providers.
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::Message;
+    ///
+    /// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
+    /// email.list_unsubscribe(
+    ///     "https://example.com/unsubscribe?id=1234",
+    ///     Some("unsubscribe@example.com"),
+    /// );
+    /// ```
+    ///
+    /// [RFC 8058]: https://www.rfc-editor.org/rfc/rfc8058
+    pub fn list_unsubscribe<T: Into<String>>(
+        &mut self,
+        url: T,
+        mailto: Option<&str>,
+    ) -> &mut Self {
+        let url = url.into();
+
+        let mut value = String::new();
+
+        if let Some(mailto) = mailto {
+            let mailto = mailto.trim_start_matches("mailto:");
+            value.push_str(&format!("<mailto:{mailto}>, "));
+        }
+
+        value.push_str(&format!("<{url}>"));
+
+        self.set_inline("headers", |content| {
+            content.headers.insert("List-Unsubscribe".to_owned(), value);
+
+            if url.starts_with("https://") {
+                content.headers.insert(
+                    "List-Unsubscribe-Post".to_owned(),
+                    "List-Unsubscribe=One-Click".to_owned(),
+                );
+            } else {
+                content.headers.remove("List-Unsubscribe-Post");
+            }
+        })
     }
 }
 
 /// Message options for a particular Message
+///
+/// Fields that are not set are omitted from the request, so the account or
+/// subaccount defaults apply, i.e. for open and click tracking.
+///
 /// ```rust
 /// # extern crate chrono;
-/// # extern crate sparkpost;
+/// # extern crate sparklepost;
 /// # fn main() {
 /// use chrono::prelude::*;
-/// use sparkpost::transmission::Options;
+/// use sparklepost::transmission::Options;
 ///
-/// let options = Options {
-///            open_tracking: false,
-///            click_tracking: false,
-///            transactional: false,
-///            sandbox: false,
-///            inline_css: false,
-///            start_time: Some(Utc.ymd(2014, 7, 8).and_hms(9, 10, 11))
-///        };
+/// let options = Options::new()
+///     .open_tracking(true)
+///     .click_tracking(true)
+///     .ip_pool("transactional")
+///     .start_time(Utc.with_ymd_and_hms(2014, 7, 8, 9, 10, 11).unwrap());
 /// // or
 /// let options2 = Options::default();
 ///
 /// # }
 ///  ```
-#[derive(Debug, Serialize, PartialEq, Default)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
+#[non_exhaustive]
 pub struct Options {
-    pub open_tracking: bool,
-    pub click_tracking: bool,
-    pub transactional: bool,
-    pub sandbox: bool,
-    pub inline_css: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_tracking: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub click_tracking: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transactional: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inline_css: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub start_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip_pool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_suppression: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perform_substitutions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_open: Option<bool>,
+}
+
+impl Options {
+    /// creates new options with all fields unset
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// track opens with a tracking pixel at the end of the HTML body
+    pub fn open_tracking(mut self, open_tracking: bool) -> Self {
+        self.open_tracking = Some(open_tracking);
+        self
+    }
+
+    /// track clicks by rewriting links in the HTML body
+    pub fn click_tracking(mut self, click_tracking: bool) -> Self {
+        self.click_tracking = Some(click_tracking);
+        self
+    }
+
+    /// mark the message as transactional, delivering it to recipients who
+    /// unsubscribed from non-transactional messages
+    pub fn transactional(mut self, transactional: bool) -> Self {
+        self.transactional = Some(transactional);
+        self
+    }
+
+    /// send from the sandbox domain, see <https://developers.sparkpost.com/api/transmissions/#header-sandbox>
+    pub fn sandbox(mut self, sandbox: bool) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// inline `<style>` rules of the HTML body into `style` attributes
+    pub fn inline_css(mut self, inline_css: bool) -> Self {
+        self.inline_css = Some(inline_css);
+        self
+    }
+
+    /// schedule the transmission, up to 31 days in the future
+    pub fn start_time(mut self, start_time: DateTime<Utc>) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// send through a dedicated IP pool
+    pub fn ip_pool<T: Into<String>>(mut self, ip_pool: T) -> Self {
+        self.ip_pool = Some(ip_pool.into());
+        self
+    }
+
+    /// ignore the suppression list, only supported by SparkPost Enterprise
+    pub fn skip_suppression(mut self, skip_suppression: bool) -> Self {
+        self.skip_suppression = Some(skip_suppression);
+        self
+    }
+
+    /// enable or disable substitutions in content, enabled by the API by
+    /// default
+    pub fn perform_substitutions(
+        mut self,
+        perform_substitutions: bool,
+    ) -> Self {
+        self.perform_substitutions = Some(perform_substitutions);
+        self
+    }
+
+    /// add a tracking pixel at the top of the HTML body as well, improving
+    /// open tracking in clients that truncate long messages
+    pub fn initial_open(mut self, initial_open: bool) -> Self {
+        self.initial_open = Some(initial_open);
+        self
+    }
 }
 
 /// Attachment data
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Attachment {
     /// Name of the file
     /// i.e. 'file_name.png'
-    name: String,
+    pub(crate) name: String,
 
     /// File mime type
     /// i.e. 'image/png'
@@ -249,18 +695,449 @@ impl Attachment {
             data: data.into(),
         }
     }
+
+    /// create attachment from raw bytes, encoding them as base64
+    ///
+    /// Fails if the encoded data exceeds [`MAX_MESSAGE_SIZE`].
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::Attachment;
+    ///
+    /// let attachment =
+    ///     Attachment::from_bytes("hello.txt", "text/plain", b"Hello!").unwrap();
+    /// ```
+    pub fn from_bytes<N: Into<String>, M: Into<String>>(
+        name: N,
+        file_type: M,
+        data: &[u8],
+    ) -> Result<Self, Error> {
+        let name = name.into();
+
+        if encoded_len(data.len()) > MAX_MESSAGE_SIZE {
+            return Err(Error::InvalidMessage(format!(
+                "attachment `{name}` exceeds the maximum message size of {MAX_MESSAGE_SIZE} bytes"
+            )));
+        }
+
+        Ok(Attachment {
+            name,
+            file_type: file_type.into(),
+            data: BASE64.encode(data),
+        })
+    }
+
+    /// create attachment from a file
+    ///
+    /// The attachment is named after the file and its MIME type is inferred
+    /// from the file extension, falling back to `application/octet-stream`.
+    /// Fails if the file cannot be read or is too large, see
+    /// [`from_bytes`](Attachment::from_bytes).
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let len = fs::metadata(path)?.len();
+        if encoded_len(usize::try_from(len).unwrap_or(usize::MAX))
+            > MAX_MESSAGE_SIZE
+        {
+            return Err(Error::InvalidMessage(format!(
+                "attachment `{name}` exceeds the maximum message size of {MAX_MESSAGE_SIZE} bytes"
+            )));
+        }
+
+        Self::from_bytes(name, mime_type(path), &fs::read(path)?)
+    }
+}
+
+/// Length of `len` bytes once base64-encoded with padding.
+fn encoded_len(len: usize) -> usize {
+    (len / 3 + usize::from(len % 3 != 0)).saturating_mul(4)
+}
+
+/// Infers a MIME type from the file extension.
+fn mime_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    match ext.as_str() {
+        "txt" | "log" => "text/plain",
+        "htm" | "html" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "ics" => "text/calendar",
+        "vcf" => "text/vcard",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "ppt" => "application/vnd.ms-powerpoint",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "odt" => "application/vnd.oasis.opendocument.text",
+        "ods" => "application/vnd.oasis.opendocument.spreadsheet",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/vnd.microsoft.icon",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Image embedded in the HTML body
+///
+/// The image is referenced from the HTML body by its name, i.e.
+/// `<img src="cid:logo.png">`.
+///
+/// ```rust
+/// use sparklepost::transmission::{InlineImage, Message};
+///
+/// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
+/// email
+///     .html(r#"<img src="cid:logo.png">"#)
+///     .add_inline_image(InlineImage::from_data(
+///         "logo.png",
+///         "image/png",
+///         "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=",
+///     ));
+/// ```
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct InlineImage {
+    /// Content-ID used to reference the image
+    /// i.e. 'logo.png'
+    pub(crate) name: String,
+
+    /// Image mime type
+    /// i.e. 'image/png'
+    #[serde(rename = "type")]
+    image_type: String,
+
+    /// base64 encoded data
+    data: String,
+}
+
+impl<'a> From<&'a InlineImage> for InlineImage {
+    fn from(image: &'a InlineImage) -> Self {
+        InlineImage {
+            name: image.name.to_owned(),
+            image_type: image.image_type.to_owned(),
+            data: image.data.to_owned(),
+        }
+    }
+}
+
+impl InlineImage {
+    pub fn from_data<T: Into<String>>(name: T, image_type: T, data: T) -> Self {
+        InlineImage {
+            name: name.into(),
+            image_type: image_type.into(),
+            data: data.into(),
+        }
+    }
 }
 
 /// Email contents
-#[derive(Debug, Serialize, Default)]
-pub(crate) struct Content {
-    pub from: EmailAddress,
-    pub subject: String,
-    pub tags: Option<Vec<String>>,
-    pub text: Option<String>,
-    pub html: Option<String>,
-    pub template_id: Option<String>,
-    pub attachments: Vec<Attachment>,
+///
+/// Content is either given inline, taken from a stored template or a
+/// pre-built RFC 822 message. Only the fields of the respective variant are
+/// sent to the API.
+///
+/// The inline content setters of [`Message`], i.e.
+/// [`subject`](Message::subject) or [`html`](Message::html), only apply to
+/// inline content; on template or RFC 822 content they have no effect and are
+/// reported by [`Message::validate`]. [`template_id`] and [`from_rfc822`]
+/// replace inline content.
+///
+/// When deserializing, objects with a `template_id` are stored templates,
+/// objects with an `email_rfc822` are RFC 822 messages and all others must be
+/// inline content with a `from` address.
+///
+/// [`template_id`]: Message::template_id
+/// [`from_rfc822`]: Message::from_rfc822
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)] // inline content is the common case
+pub enum Content {
+    /// stored template, see <https://developers.sparkpost.com/api/transmissions/#header-stored-template-content>
+    Template {
+        template_id: String,
+        /// use the draft instead of the published version of the template
+        use_draft_template: bool,
+    },
+
+    /// raw MIME message, see [`Message::from_rfc822`]
+    Rfc822 { email_rfc822: String },
+
+    /// sender, subject and body given inline, built with [`Message`] setters
+    ///
+    /// [`InlineContent`] cannot be constructed by hand, it is only read from
+    /// deserialized content, i.e. of [`TransmissionDetails`].
+    ///
+    /// [`TransmissionDetails`]: super::TransmissionDetails
+    Inline(InlineContent),
+}
+
+impl Default for Content {
+    fn default() -> Self {
+        Content::Inline(InlineContent::new(EmailAddress::default()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Content {
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Template {
+            template_id: String,
+            #[serde(default)]
+            use_draft_template: bool,
+        }
+
+        #[derive(Deserialize)]
+        struct Rfc822 {
+            email_rfc822: String,
+        }
+
+        let object = Map::<String, Value>::deserialize(deserializer)?;
+
+        let content = if object.contains_key("template_id") {
+            Template::deserialize(Value::Object(object)).map(|template| {
+                Content::Template {
+                    template_id: template.template_id,
+                    use_draft_template: template.use_draft_template,
+                }
+            })
+        } else if object.contains_key("email_rfc822") {
+            Rfc822::deserialize(Value::Object(object)).map(|raw| {
+                Content::Rfc822 {
+                    email_rfc822: raw.email_rfc822,
+                }
+            })
+        } else {
+            InlineContent::deserialize(Value::Object(object))
+                .map(Content::Inline)
+        };
+
+        content.map_err(de::Error::custom)
+    }
+}
+
+/// Inline email contents
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InlineContent {
+    #[serde(deserialize_with = "deserialize_from")]
+    pub(crate) from: EmailAddress,
+    #[serde(default)]
+    pub(crate) subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) html: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) amp_html: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) attachments: Vec<Attachment>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) inline_images: Vec<InlineImage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) reply_to: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) headers: BTreeMap<String, String>,
+}
+
+impl InlineContent {
+    pub(crate) fn new(from: EmailAddress) -> Self {
+        InlineContent {
+            from,
+            subject: String::new(),
+            tags: None,
+            text: None,
+            html: None,
+            amp_html: None,
+            attachments: Vec::new(),
+            inline_images: Vec::new(),
+            reply_to: None,
+            headers: BTreeMap::new(),
+        }
+    }
+
+    /// sender address
+    pub fn from(&self) -> &EmailAddress {
+        &self.from
+    }
+
+    /// subject
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// tags shown in events for all recipients
+    pub fn tags(&self) -> &[String] {
+        self.tags.as_deref().unwrap_or_default()
+    }
+
+    /// text body
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// HTML body
+    pub fn html(&self) -> Option<&str> {
+        self.html.as_deref()
+    }
+
+    /// AMP HTML body
+    pub fn amp_html(&self) -> Option<&str> {
+        self.amp_html.as_deref()
+    }
+
+    /// attachments
+    pub fn attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+
+    /// images embedded in the HTML body
+    pub fn inline_images(&self) -> &[InlineImage] {
+        &self.inline_images
+    }
+
+    /// `Reply-To` address
+    pub fn reply_to(&self) -> Option<&str> {
+        self.reply_to.as_deref()
+    }
+
+    /// custom email headers
+    pub fn headers(&self) -> &BTreeMap<String, String> {
+        &self.headers
+    }
+}
+
+/// Reads the sender given either as address object or as string, which is
+/// split into address and display name if possible, i.e.
+/// `Example <marketing@example.com>`, and kept as is otherwise.
+fn deserialize_from<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<EmailAddress, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Sender {
+        String(String),
+        Object(EmailAddress),
+    }
+
+    Ok(match Sender::deserialize(deserializer)? {
+        Sender::String(from) => {
+            EmailAddress::parse(&from).unwrap_or_else(|_| from.into())
+        }
+        Sender::Object(from) => from,
+    })
+}
+
+/// Appends `recipient`, removing recipients with the same normalized address
+/// and recording them in `merged`.
+fn merge_into(
+    list: &mut Vec<Recipient>,
+    recipient: Recipient,
+    normalization: Normalization,
+    merged: &mut Vec<MergedRecipient>,
+) {
+    let key = normalization.normalize(&recipient.address.email);
+
+    list.retain(|rec| {
+        if normalization.normalize(&rec.address.email) != key {
+            return true;
+        }
+
+        // re-adding the exact same address is not a merge
+        if rec.address.email != recipient.address.email {
+            merged.push(MergedRecipient {
+                replaced: rec.address.email.clone(),
+                replacement: recipient.address.email.clone(),
+            });
+        }
+
+        false
+    });
+
+    list.push(recipient);
+}
+
+/// Deserialized form of [`Message`], restoring how recipients are addressed
+#[derive(Deserialize)]
+struct MessageData {
+    #[serde(default)]
+    options: Options,
+    description: Option<String>,
+    campaign_id: Option<String>,
+    return_path: Option<String>,
+    metadata: Option<Value>,
+    substitution_data: Option<Value>,
+    #[serde(default)]
+    recipients: RecipientSet,
+    #[serde(default)]
+    content: Content,
+}
+
+impl From<MessageData> for Message {
+    fn from(data: MessageData) -> Self {
+        let mut message = Message {
+            options: data.options,
+            description: data.description,
+            campaign_id: data.campaign_id,
+            return_path: data.return_path,
+            metadata: data.metadata,
+            substitution_data: data.substitution_data,
+            recipients: data.recipients,
+            content: data.content,
+            normalization: Normalization::default(),
+            merged: Vec::new(),
+            ignored_fields: Vec::new(),
+        };
+
+        // recipients with `header_to` were added as CC or BCC, CC recipients
+        // are listed in the `CC` header of inline content, and without any
+        // "To" recipient `header_to` is unset
+        let cc = match message.content {
+            Content::Inline(ref content) => {
+                content.headers.get("CC").cloned().unwrap_or_default()
+            }
+            Content::Template { .. } | Content::Rfc822 { .. } => String::new(),
+        };
+
+        if let RecipientSet::LocalList(ref mut list) = message.recipients {
+            for rec in list.iter_mut() {
+                let is_cc = cc.split(',').any(|e| e == rec.address.email);
+
+                rec.kind = match rec.address.header_to {
+                    None if is_cc => RecipientKind::Cc,
+                    None => RecipientKind::To,
+                    Some(_) if is_cc => RecipientKind::Cc,
+                    Some(_) => RecipientKind::Bcc,
+                };
+            }
+        }
+
+        message
+    }
 }
 
 #[cfg(test)]
@@ -295,10 +1172,8 @@ mod test {
             "test@test.com",
             json_value["content"]["from"]["email"].as_str().unwrap()
         );
-        assert!(!json_value["options"]["sandbox"].as_bool().unwrap());
-        assert!(!json_value["options"]["click_tracking"].as_bool().unwrap());
-        assert!(!json_value["options"]["open_tracking"].as_bool().unwrap());
-        assert!(!json_value["options"]["transactional"].as_bool().unwrap());
+        // unset options inherit the account defaults
+        assert_eq!(json_value["options"], serde_json::json!({}));
         // println!("{:#?}", json_value);
     }
 
@@ -306,16 +1181,14 @@ mod test {
     fn create_message_with_options() {
         let email: Message = Message::with_options(
             "test@test.com".into(),
-            Options {
-                open_tracking: true,
-                click_tracking: true,
-                transactional: true,
-                sandbox: true,
-                inline_css: false,
-                start_time: Some(
+            Options::new()
+                .open_tracking(true)
+                .click_tracking(true)
+                .transactional(true)
+                .sandbox(true)
+                .start_time(
                     Utc.with_ymd_and_hms(2014, 7, 8, 9, 10, 11).unwrap(),
                 ),
-            },
         );
         let json_value = to_value(email).unwrap();
         // println!("{:?}", &json_value);
@@ -328,7 +1201,7 @@ mod test {
         assert!(json_value["options"]["click_tracking"].as_bool().unwrap());
         assert!(json_value["options"]["open_tracking"].as_bool().unwrap());
         assert!(json_value["options"]["transactional"].as_bool().unwrap());
-        assert!(!json_value["options"]["inline_css"].as_bool().unwrap());
+        assert!(json_value["options"].get("inline_css").is_none());
         assert_eq!("2014-07-08T09:10:11Z", json_value["options"]["start_time"]);
     }
 
@@ -340,10 +1213,10 @@ mod test {
             any_field: "any_value".into(),
         };
 
-        email.add_recipient(Recipient {
-            address: "name@domain.com".into(),
-            substitution_data: Some(to_value(data).unwrap()),
-        });
+        email.add_recipient(
+            Recipient::try_with_substitution("name@domain.com".into(), data)
+                .unwrap(),
+        );
         let json_value = to_value(email).unwrap();
         // println!("{:#?}", &json_value);
 
@@ -368,15 +1241,12 @@ mod test {
             _ => unreachable!(),
         };
 
-        message.add_recipient(Recipient {
-            address: "email@domain.com".into(),
-            substitution_data: Some(
-                to_value(Substitute {
-                    any_field: "any_value".into(),
-                })
-                .unwrap(),
-            ),
-        });
+        message.add_recipient(Recipient::with_substitution(
+            "email@domain.com".into(),
+            Substitute {
+                any_field: "any_value".into(),
+            },
+        ));
 
         match message.recipients {
             RecipientSet::LocalList(ref list) => {
@@ -401,12 +1271,605 @@ mod test {
         assert_eq!(json_value["recipients"]["list_id"], "mylist");
     }
 
+    #[test]
+    fn try_substitution_data_and_metadata() {
+        use std::collections::HashMap;
+
+        let mut email = Message::default();
+        email
+            .try_substitution_data(Substitute {
+                any_field: "any_value".into(),
+            })
+            .unwrap()
+            .try_metadata(Substitute {
+                any_field: "meta".into(),
+            })
+            .unwrap();
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(json_value["substitution_data"]["any_field"], "any_value");
+        assert_eq!(json_value["metadata"]["any_field"], "meta");
+
+        // JSON object keys must be strings
+        let mut bad_data = HashMap::new();
+        bad_data.insert((1, 2), "value");
+
+        let err = email.try_substitution_data(&bad_data).unwrap_err();
+        assert!(matches!(err, Error::Serialization(_)));
+        let err = email.try_metadata(&bad_data).unwrap_err();
+        assert!(matches!(err, Error::Serialization(_)));
+
+        // previous values are kept on error
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(json_value["substitution_data"]["any_field"], "any_value");
+    }
+
+    #[test]
+    fn cc_and_bcc_recipients() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .add_recipient(EmailAddress::new("to@example.com", "To"))
+            .add_cc("cc@example.com")
+            .add_bcc("bcc@example.com")
+            .add_recipient("to2@example.com")
+            .add_cc("cc2@example.com");
+
+        let json_value = to_value(&email).unwrap();
+
+        assert_eq!(
+            json_value["recipients"],
+            serde_json::json!([
+                {
+                    "address": {
+                        "email": "to@example.com",
+                        "name": "To"
+                    },
+                    "substitution_data": null
+                },
+                {
+                    "address": {
+                        "email": "cc@example.com",
+                        "name": null,
+                        "header_to": "to@example.com,to2@example.com"
+                    },
+                    "substitution_data": null
+                },
+                {
+                    "address": {
+                        "email": "bcc@example.com",
+                        "name": null,
+                        "header_to": "to@example.com,to2@example.com"
+                    },
+                    "substitution_data": null
+                },
+                {
+                    "address": {
+                        "email": "to2@example.com",
+                        "name": null
+                    },
+                    "substitution_data": null
+                },
+                {
+                    "address": {
+                        "email": "cc2@example.com",
+                        "name": null,
+                        "header_to": "to@example.com,to2@example.com"
+                    },
+                    "substitution_data": null
+                }
+            ])
+        );
+        assert_eq!(
+            json_value["content"]["headers"],
+            serde_json::json!({ "CC": "cc@example.com,cc2@example.com" })
+        );
+    }
+
+    #[test]
+    fn cc_header_removed() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .add_recipient("to@example.com")
+            .add_bcc("bcc@example.com");
+
+        // BCC recipients never appear in headers
+        let json_value = to_value(&email).unwrap();
+        assert!(json_value["content"].get("headers").is_none());
+
+        email.add_cc("cc@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(json_value["content"]["headers"]["CC"], "cc@example.com");
+
+        // re-adding as "To" recipient drops the CC entry
+        email.add_recipient("cc@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert!(json_value["content"].get("headers").is_none());
+        assert!(json_value["recipients"][2]["address"]
+            .get("header_to")
+            .is_none());
+
+        email.add_cc("cc@example.com").recipient_list("my_list");
+        let json_value = to_value(&email).unwrap();
+        assert!(json_value["content"].get("headers").is_none());
+    }
+
+    #[test]
+    fn cc_without_to_recipient() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .subject("Hi")
+            .text("Hi!")
+            .add_cc("cc@example.com")
+            .add_bcc("bcc@example.com");
+
+        let json_value = to_value(&email).unwrap();
+        assert!(json_value["recipients"][0]["address"]
+            .get("header_to")
+            .is_none());
+        assert!(json_value["recipients"][1]["address"]
+            .get("header_to")
+            .is_none());
+        assert_eq!(json_value["content"]["headers"]["CC"], "cc@example.com");
+
+        email.add_recipient("to@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["recipients"][0]["address"]["header_to"],
+            "to@example.com"
+        );
+    }
+
+    #[test]
+    fn reply_to_and_headers() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .reply_to("Support <support@example.com>")
+            .header("X-Customer-Campaign-ID", "big_sale")
+            .header("X-Customer-Campaign-ID", "bigger_sale")
+            .add_recipient("to@example.com")
+            .add_cc("cc@example.com");
+
+        let json_value = to_value(&email).unwrap();
+
+        assert_eq!(
+            json_value["content"]["reply_to"],
+            "Support <support@example.com>"
+        );
+        assert_eq!(
+            json_value["content"]["headers"],
+            serde_json::json!({
+                "CC": "cc@example.com",
+                "X-Customer-Campaign-ID": "bigger_sale"
+            })
+        );
+    }
+
+    #[test]
+    fn list_unsubscribe() {
+        let mut email = Message::new("sender@example.com");
+        email.list_unsubscribe(
+            "https://example.com/unsubscribe?id=1",
+            Some("unsubscribe@example.com"),
+        );
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["content"]["headers"],
+            serde_json::json!({
+                "List-Unsubscribe": "<mailto:unsubscribe@example.com>, <https://example.com/unsubscribe?id=1>",
+                "List-Unsubscribe-Post": "List-Unsubscribe=One-Click"
+            })
+        );
+
+        // one-click unsubscribe requires HTTPS
+        email.list_unsubscribe(
+            "http://example.com/unsubscribe?id=1",
+            Some("mailto:unsubscribe@example.com"),
+        );
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["content"]["headers"],
+            serde_json::json!({
+                "List-Unsubscribe": "<mailto:unsubscribe@example.com>, <http://example.com/unsubscribe?id=1>"
+            })
+        );
+
+        assert!(json_value["content"].get("reply_to").is_none());
+    }
+
+    #[test]
+    fn inline_images() {
+        let mut email = Message::new("sender@example.com");
+        email.html(r#"<img src="cid:logo.png">"#).add_inline_image(
+            InlineImage::from_data("logo.png", "image/png", "aGVsbG8="),
+        );
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["content"]["inline_images"],
+            serde_json::json!([
+                { "name": "logo.png", "type": "image/png", "data": "aGVsbG8=" }
+            ])
+        );
+
+        let email = Message::new("sender@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert!(json_value["content"].get("inline_images").is_none());
+    }
+
+    #[test]
+    fn amp_html() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .amp_html("<html ⚡4email></html>")
+            .html("<html></html>");
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(json_value["content"]["amp_html"], "<html ⚡4email></html>");
+        assert_eq!(json_value["content"]["html"], "<html></html>");
+
+        let email = Message::new("sender@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert!(json_value["content"].get("amp_html").is_none());
+    }
+
+    #[test]
+    fn rfc822_content() {
+        let raw = "From: sender@example.com\r\nSubject: Hi\r\n\r\nHi!\r\n";
+
+        let mut email = Message::from_rfc822(raw);
+        email
+            .add_recipient("to@example.com")
+            .add_cc("cc@example.com")
+            .campaign_id("raw");
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["content"],
+            serde_json::json!({ "email_rfc822": raw })
+        );
+        assert_eq!(
+            json_value["recipients"][1]["address"]["header_to"],
+            "to@example.com"
+        );
+        assert_eq!(json_value["campaign_id"], "raw");
+
+        // inline fields do not replace the raw message
+        email.subject("Inline subject").header("X-Campaign", "sale");
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["content"],
+            serde_json::json!({ "email_rfc822": raw })
+        );
+        assert_eq!(email.ignored_fields(), ["subject", "headers"]);
+    }
+
+    #[test]
+    fn template_content() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .add_recipient("to@example.com")
+            .add_cc("cc@example.com")
+            .header("X-Campaign", "sale")
+            .template_id("offer");
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["content"],
+            serde_json::json!({
+                "template_id": "offer",
+                "use_draft_template": false
+            })
+        );
+        assert_eq!(
+            json_value["recipients"][1]["address"]["header_to"],
+            "to@example.com"
+        );
+
+        email.content(Content::Template {
+            template_id: "offer".to_owned(),
+            use_draft_template: true,
+        });
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(json_value["content"]["use_draft_template"], true);
+
+        let json_value = to_value(Message::new("sender@example.com")).unwrap();
+        assert_eq!(
+            json_value["content"],
+            serde_json::json!({
+                "from": { "email": "sender@example.com", "name": null },
+                "subject": ""
+            })
+        );
+    }
+
+    #[test]
+    fn attachment_from_bytes() {
+        let attachment =
+            Attachment::from_bytes("hello.txt", "text/plain", b"hello")
+                .unwrap();
+        assert_eq!(
+            to_value(&attachment).unwrap(),
+            serde_json::json!({
+                "name": "hello.txt",
+                "type": "text/plain",
+                "data": "aGVsbG8="
+            })
+        );
+
+        let data = vec![0; MAX_MESSAGE_SIZE / 4 * 3 + 1];
+        let err = Attachment::from_bytes(
+            "big.bin",
+            "application/octet-stream",
+            &data,
+        )
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidMessage(_)));
+        assert!(err.to_string().contains("big.bin"));
+    }
+
+    #[test]
+    fn attachment_from_path() {
+        let path = std::env::temp_dir()
+            .join(format!("sparklepost-{}-Report.PDF", std::process::id()));
+        fs::write(&path, b"%PDF").unwrap();
+
+        let attachment = Attachment::from_path(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(attachment.file_type, "application/pdf");
+        assert_eq!(attachment.data, "JVBERg==");
+        assert!(attachment.name.ends_with("-Report.PDF"));
+
+        let err = Attachment::from_path(&path).unwrap_err();
+        assert!(matches!(err, Error::Io(_)));
+
+        assert_eq!(mime_type(Path::new("notes")), "application/octet-stream");
+        assert_eq!(mime_type(Path::new("a/photo.jpeg")), "image/jpeg");
+    }
+
+    #[test]
+    fn options_builder() {
+        let options = Options::new()
+            .open_tracking(true)
+            .ip_pool("transactional")
+            .skip_suppression(false)
+            .perform_substitutions(false)
+            .initial_open(true);
+
+        assert_eq!(
+            to_value(&options).unwrap(),
+            serde_json::json!({
+                "open_tracking": true,
+                "ip_pool": "transactional",
+                "skip_suppression": false,
+                "perform_substitutions": false,
+                "initial_open": true
+            })
+        );
+
+        let mut email = Message::new("sender@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert!(json_value.get("return_path").is_none());
+        assert!(json_value["options"].get("ip_pool").is_none());
+
+        email.return_path("bounces@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(json_value["return_path"], "bounces@example.com");
+    }
+
+    #[test]
+    fn normalized_deduplication() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .add_recipient("Wilma@Example.com")
+            .add_cc("wilma@example.com")
+            .add_recipient("wilma@EXAMPLE.com")
+            .add_recipient("fred+news@example.com");
+
+        // domains are always compared case-insensitively
+        assert_eq!(
+            email.merged_recipients(),
+            [MergedRecipient {
+                replaced: "wilma@example.com".to_owned(),
+                replacement: "wilma@EXAMPLE.com".to_owned(),
+            }]
+        );
+
+        email.normalization(
+            Normalization::new()
+                .fold_local_part(true)
+                .strip_plus_tag(true),
+        );
+        email.add_recipient("Fred@example.com");
+
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(
+            json_value["recipients"],
+            serde_json::json!([
+                {
+                    "address": { "email": "wilma@EXAMPLE.com", "name": null },
+                    "substitution_data": null
+                },
+                {
+                    "address": { "email": "Fred@example.com", "name": null },
+                    "substitution_data": null
+                }
+            ])
+        );
+        assert_eq!(email.merged_recipients().len(), 3);
+        assert_eq!(email.merged_recipients()[1].replaced, "Wilma@Example.com");
+        assert_eq!(
+            email.merged_recipients()[2].replaced,
+            "fred+news@example.com"
+        );
+
+        // merges are forgotten along with the replaced recipients
+        email.recipient_list("my_list");
+        assert!(email.merged_recipients().is_empty());
+
+        email
+            .add_recipient("wilma@example.com")
+            .add_recipient("Wilma@example.com");
+        assert_eq!(email.merged_recipients().len(), 1);
+    }
+
+    fn round_trip(email: &Message) -> Message {
+        let json_value = to_value(email).unwrap();
+        let email: Message =
+            serde_json::from_value(json_value.clone()).unwrap();
+        assert_eq!(to_value(&email).unwrap(), json_value);
+        email
+    }
+
+    #[test]
+    fn deserialize_inline_message() {
+        let mut email = Message::with_options(
+            EmailAddress::new("sender@example.com", "Sender"),
+            Options::new()
+                .open_tracking(true)
+                .ip_pool("transactional")
+                .start_time(
+                    Utc.with_ymd_and_hms(2014, 7, 8, 9, 10, 11).unwrap(),
+                ),
+        );
+        email
+            .add_recipient(
+                Recipient::try_with_substitution(
+                    EmailAddress::new("to@example.com", "To"),
+                    Substitute {
+                        any_field: "any_value".into(),
+                    },
+                )
+                .unwrap()
+                .tag("welcome")
+                .metadata(serde_json::json!({ "user_id": 42 })),
+            )
+            .add_cc("cc@example.com")
+            .add_bcc("bcc@example.com")
+            .subject("Hi")
+            .text("Hi!")
+            .html(r#"<img src="cid:logo.png">"#)
+            .amp_html("<html ⚡4email></html>")
+            .reply_to("support@example.com")
+            .header("X-Campaign", "sale")
+            .add_attachment(Attachment::from_data(
+                "a.txt",
+                "text/plain",
+                "aGk=",
+            ))
+            .add_inline_image(InlineImage::from_data(
+                "logo.png",
+                "image/png",
+                "aGk=",
+            ))
+            .campaign_id("campaign")
+            .return_path("bounces@example.com")
+            .metadata(serde_json::json!({ "source": "test" }))
+            .substitution_data(serde_json::json!({ "name": "Wilma" }));
+        email.description = Some("description".to_owned());
+
+        let mut email = round_trip(&email);
+
+        // CC and BCC recipients are restored
+        email.add_recipient("to2@example.com");
+        let json_value = to_value(&email).unwrap();
+        assert_eq!(json_value["content"]["headers"]["CC"], "cc@example.com");
+        assert_eq!(
+            json_value["recipients"][2]["address"]["header_to"],
+            "to@example.com,to2@example.com"
+        );
+    }
+
+    #[test]
+    fn deserialize_template_and_rfc822_messages() {
+        let mut email = Message::default();
+        email.recipient_list("my_list").content(Content::Template {
+            template_id: "offer".to_owned(),
+            use_draft_template: true,
+        });
+        let email = round_trip(&email);
+        assert!(matches!(
+            email.content,
+            Content::Template {
+                use_draft_template: true,
+                ..
+            }
+        ));
+
+        let mut email = Message::from_rfc822("Subject: Hi\r\n\r\nHi!\r\n");
+        email.add_recipient("to@example.com");
+        let email = round_trip(&email);
+        assert!(matches!(email.content, Content::Rfc822 { .. }));
+
+        let email: Message = serde_json::from_str(
+            r#"{
+                "recipients": [{ "address": { "email": "to@example.com" } }],
+                "content": { "template_id": "offer" }
+            }"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            email.content,
+            Content::Template {
+                use_draft_template: false,
+                ..
+            }
+        ));
+        assert_eq!(email.options, Options::default());
+    }
+
+    #[test]
+    fn deserialize_content() {
+        let content: Content = serde_json::from_value(serde_json::json!({
+            "from": "Example <marketing@example.com>",
+            "subject": "Hi",
+            "text": "Hi!"
+        }))
+        .unwrap();
+        match content {
+            Content::Inline(content) => assert_eq!(
+                content.from,
+                EmailAddress::new("marketing@example.com", "Example")
+            ),
+            _ => panic!("expected inline content, got {content:?}"),
+        }
+
+        let content: Content = serde_json::from_value(serde_json::json!({
+            "from": "{{sender}}",
+            "subject": "Hi"
+        }))
+        .unwrap();
+        assert!(matches!(
+            content,
+            Content::Inline(InlineContent { ref from, .. })
+                if from.email == "{{sender}}"
+        ));
+
+        // objects matching no variant are refused
+        let err = serde_json::from_value::<Content>(
+            serde_json::json!({ "bogus": 1 }),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("missing field `from`"));
+
+        let err = serde_json::from_value::<Message>(serde_json::json!({
+            "content": { "template_id": 1 }
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("invalid type"));
+    }
+
     #[test]
     fn create_options() {
         let options = Options::default();
-        assert!(!options.click_tracking);
-        assert!(!options.open_tracking);
-        assert!(!options.sandbox);
-        assert!(!options.transactional);
+        assert_eq!(options.click_tracking, None);
+        assert_eq!(options.open_tracking, None);
+        assert_eq!(options.sandbox, None);
+        assert_eq!(options.transactional, None);
+
+        let options = options.click_tracking(false);
+        assert_eq!(options.click_tracking, Some(false));
     }
 }
@@ -2,8 +2,9 @@
 //!
 //! # Examples
 //!
-//! ```
-//! use sparkpost::transmission::{Transmission, Message, EmailAddress, TransmissionResponse};
+//! ```no_run
+//! # #[cfg(feature = "blocking")] {
+//! use sparklepost::transmission::{Transmission, Message, EmailAddress};
 //!
 //! let tm = Transmission::new("api_key");
 //! // to create for EU version use
@@ -17,197 +18,688 @@
 //!      .html("<h1>html body of the email</h1>")
 //!      .text("text body of the email");
 //!
-//! let result = tm.send(&email);
-//!
-//! match result {
-//!    Ok(res) => {
-//!         println!("{:?}", &res);
-//!         match res {
-//!             TransmissionResponse::ApiResponse(api_res) => {
-//!              //   assert_eq!(1, api_res.total_accepted_recipients);
-//!              //   assert_eq!(0, api_res.total_rejected_recipients);
-//!             }
-//!             TransmissionResponse::ApiError(errors) => {
-//!                 println!("res: \n {:#?}", &errors);
-//!             }
-//!         }
+//! match tm.send(&email) {
+//!     Ok(res) => {
+//!         println!("accepted: {}", res.total_accepted_recipients);
 //!     }
 //!     Err(error) => {
-//!         println!("error \n {:#?}", error);
+//!         println!("error \n {:#?}", error.api_errors());
 //!     }
 //! }
+//! # }
 //! ```
+//!
+//! With the `async` feature enabled, [`AsyncTransmission`] provides the same
+//! API for use on an async runtime.
 
+#[cfg(feature = "blocking")]
+use reqwest::blocking::{Client, RequestBuilder};
+#[cfg(any(feature = "blocking", feature = "async"))]
 use reqwest::{
-    blocking::Client,
     header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE},
-    Error,
+    StatusCode,
 };
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::Serialize;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde::{de::DeserializeOwned, Deserialize};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde_json::Value;
 
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::{retry::retry_after, Error};
+#[cfg(feature = "blocking")]
+use crate::{RateLimiter, RetryPolicy};
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod builder;
 mod message;
 mod models;
+#[cfg(feature = "async")]
+mod nonblocking;
+mod render;
+mod response;
+#[cfg(test)]
+mod testing;
+mod validation;
 
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use self::builder::TransmissionBuilder;
 pub use self::message::*;
 pub use self::models::*;
+#[cfg(feature = "async")]
+pub use self::nonblocking::AsyncTransmission;
+pub use self::render::{render_template, RenderError, RenderedContent};
+#[cfg(feature = "blocking")]
+use self::response::TransmissionWrapper;
+pub use self::response::{
+    ApiResponse, TransmissionDetails, TransmissionState, TransmissionSummary,
+};
+pub use self::validation::ValidationError;
+pub use crate::error::ApiError;
 
-/// Reqwest Error
-pub type ReqError = Error;
+#[cfg(any(feature = "blocking", feature = "async"))]
+const GLOBAL_BASE_URL: &str = "https://api.sparkpost.com/api/v1";
+#[cfg(any(feature = "blocking", feature = "async"))]
+const EU_BASE_URL: &str = "https://api.eu.sparkpost.com/api/v1";
 
-/// Transmission result returned by the API
+/// Filters for listing transmissions, sent as query parameters
 ///
-#[derive(Debug, Deserialize)]
-pub struct ApiResponse {
-    pub total_rejected_recipients: usize,
-    pub total_accepted_recipients: usize,
-    pub id: String,
+/// ```
+/// use sparklepost::transmission::ListTransmissionsQuery;
+///
+/// let query = ListTransmissionsQuery::new()
+///     .campaign_id("thanksgiving")
+///     .template_id("christmas_offer");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ListTransmissionsQuery {
+    /// only return transmissions with this campaign id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub campaign_id: Option<String>,
+
+    /// only return transmissions using this template
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<String>,
+}
+
+impl ListTransmissionsQuery {
+    /// creates new query without filters, matching all transmissions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// filter by campaign id
+    pub fn campaign_id<T: Into<String>>(mut self, campaign_id: T) -> Self {
+        self.campaign_id = Some(campaign_id.into());
+        self
+    }
+
+    /// filter by template id
+    pub fn template_id<T: Into<String>>(mut self, template_id: T) -> Self {
+        self.template_id = Some(template_id.into());
+        self
+    }
 }
 
-/// Transmission error returned by the API
-/// #### Note
-/// this is not http error
+/// Successful response body, i.e. `{ "results": ... }`
+#[cfg(any(feature = "blocking", feature = "async"))]
 #[derive(Debug, Deserialize)]
-pub struct ApiError {
-    pub description: Option<String>,
-    pub code: Option<String>,
-    pub message: Option<String>,
+struct Results<T> {
+    results: T,
 }
 
-/// Wrapper Enum for ApiResponse and ApiError
+/// Error response body, i.e. `{ "errors": [...] }`
+#[cfg(any(feature = "blocking", feature = "async"))]
 #[derive(Debug, Deserialize)]
-pub enum TransmissionResponse {
-    #[serde(rename = "results")]
-    ApiResponse(ApiResponse),
-    #[serde(rename = "errors")]
-    ApiError(Vec<ApiError>),
+struct Errors {
+    errors: Vec<ApiError>,
 }
 
-/// Sparkpost Transmission
+/// Sparkpost Transmission, using a blocking HTTP client
 ///
 /// Currently only supports sending email message.
 ///
 /// ```
-/// use sparkpost::transmission::Transmission;
+/// use sparklepost::transmission::Transmission;
 /// let tm = Transmission::new("api_key_form_env".to_string());
 /// ```
 ///
+/// Use [`Transmission::builder`] to configure the base URL, timeouts, proxy
+/// or HTTP client.
+///
 /// For more info see <https://developers.sparkpost.com/api/transmissions>.
+#[cfg(feature = "blocking")]
 #[derive(Debug)]
 pub struct Transmission {
-    api_key: String,
-    url: String,
-    client: Client,
+    pub(super) api_key: String,
+    pub(super) base_url: String,
+    pub(super) url: String,
+    pub(super) client: Client,
+    pub(super) retry_policy: Option<RetryPolicy>,
+    pub(super) rate_limiter: Option<RateLimiter>,
+    pub(super) validate: bool,
 }
 
+#[cfg(feature = "blocking")]
 impl Transmission {
     /// creates new Transmission with api key for global version
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, see
+    /// [`reqwest::blocking::Client::new`].
     pub fn new<T: Into<String>>(api_key: T) -> Self {
-        Transmission {
-            api_key: api_key.into(),
-            url: "https://api.sparkpost.com/api/v1/transmissions".to_owned(),
-            client: Client::new(),
-        }
+        Self::builder(api_key)
+            .build()
+            .expect("HTTP client cannot be initialized")
     }
 
     /// creates new Transmission with api key for EU version
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, see
+    /// [`reqwest::blocking::Client::new`].
     pub fn new_eu<T: Into<String>>(api_key: T) -> Self {
-        Transmission {
-            api_key: api_key.into(),
-            url: "https://api.eu.sparkpost.com/api/v1/transmissions".to_owned(),
-            client: Client::new(),
-        }
+        Self::builder(api_key)
+            .eu()
+            .build()
+            .expect("HTTP client cannot be initialized")
+    }
+
+    /// creates new [`TransmissionBuilder`] with api key
+    pub fn builder<T: Into<String>>(api_key: T) -> TransmissionBuilder {
+        TransmissionBuilder::new(api_key)
     }
+
     /// Send api request
-    pub fn send(
-        &self,
-        message: &Message,
-    ) -> Result<TransmissionResponse, ReqError> {
-        self.client
+    pub fn send(&self, message: &Message) -> Result<ApiResponse, Error> {
+        let req = self
+            .client
             .post(&self.url)
-            .headers(self.construct_headers(None))
-            .json(message)
-            .send()?
-            .json()
+            .headers(construct_headers(&self.api_key)?)
+            .body(message.to_request_body(self.validate)?);
+
+        self.execute(req, false)
     }
     /// Retrieve a Scheduled Transmission from API
     pub fn scheduled_by_id(
         &self,
         transmission_id: &str,
-    ) -> Result<TransmissionResponse, ReqError> {
-        let url = format!("{}/{transmission_id}", self.url);
+    ) -> Result<TransmissionDetails, Error> {
+        let url =
+            format!("{}/{}", self.url, encode_path_segment(transmission_id));
 
-        self.client
+        let req = self
+            .client
             .get(url)
-            .headers(self.construct_headers(None))
-            .send()?
-            .json()
+            .headers(construct_headers(&self.api_key)?);
+
+        self.execute(req, true)
+            .map(|TransmissionWrapper { transmission }| transmission)
     }
 
     /// Retrieve all Scheduled Transmissions from API
     ///
     /// Example
-    /// ```rust
-    /// use sparkpost::transmission::Transmission;
-    /// use std::collections::HashMap;
+    /// ```no_run
+    /// use sparklepost::transmission::{ListTransmissionsQuery, Transmission};
     /// let tm = Transmission::new("api_key");
     ///
-    /// let mut headers = HashMap::new();
-    /// headers.insert("campaign_id", "your_campaingn_id");
-    /// headers.insert("template_id", "your_template_id");
-    ///
-    /// // filter with headers
-    /// let transmissions = tm.scheduled_transmissions(Some(&headers));
+    /// // filter by campaign and template
+    /// let query = ListTransmissionsQuery::new()
+    ///     .campaign_id("your_campaign_id")
+    ///     .template_id("your_template_id");
+    /// let transmissions = tm.scheduled_transmissions(&query);
     ///
     /// // or for all transmissions
-    /// let transmissions = tm.scheduled_transmissions(None);
-    ///
+    /// let transmissions =
+    ///     tm.scheduled_transmissions(&ListTransmissionsQuery::default());
     /// ```
     pub fn scheduled_transmissions(
         &self,
-        header_map: Option<&HashMap<&'static str, &str>>,
-    ) -> Result<TransmissionResponse, ReqError> {
-        self.client
+        query: &ListTransmissionsQuery,
+    ) -> Result<Vec<TransmissionSummary>, Error> {
+        let req = self
+            .client
             .get(&self.url)
-            .headers(self.construct_headers(header_map))
-            .send()?
-            .json()
+            .headers(construct_headers(&self.api_key)?)
+            .query(query);
+
+        self.execute(req, true)
     }
 
-    fn construct_headers(
+    /// Delete a scheduled transmission that has not started generating
+    ///
+    /// Fails with [`Error::is_not_found`] if there is no such transmission and
+    /// with [`Error::is_conflict`] if it is already generating.
+    pub fn delete(&self, transmission_id: &str) -> Result<(), Error> {
+        let url =
+            format!("{}/{}", self.url, encode_path_segment(transmission_id));
+
+        let req = self
+            .client
+            .delete(url)
+            .headers(construct_headers(&self.api_key)?);
+
+        self.execute(req, true)
+    }
+
+    /// Delete all scheduled transmissions of a campaign that have not started
+    /// generating
+    ///
+    /// Fails with [`Error::is_conflict`] if any of them is already generating.
+    pub fn delete_by_campaign(&self, campaign_id: &str) -> Result<(), Error> {
+        let req = self
+            .client
+            .delete(&self.url)
+            .headers(construct_headers(&self.api_key)?)
+            .query(&[("campaign_id", campaign_id)]);
+
+        self.execute(req, true)
+    }
+
+    /// Sends request, retrying according to the retry policy and waiting for
+    /// the rate limiter before every attempt.
+    pub(crate) fn execute<T: DeserializeOwned>(
         &self,
-        header_map: Option<&HashMap<&'static str, &str>>,
-    ) -> HeaderMap {
-        let mut headers = HeaderMap::new();
-        headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
-        headers
-            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&self.api_key).unwrap(),
-        );
+        mut req: RequestBuilder,
+        idempotent: bool,
+    ) -> Result<T, Error> {
+        let mut attempts = 1;
 
-        if let Some(header_map) = header_map {
-            for (name, value) in header_map {
-                headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        loop {
+            let next = req.try_clone();
+
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire();
+            }
+
+            let err = match Self::execute_once(req) {
+                Ok(res) => return Ok(res),
+                Err(err) => err,
+            };
+
+            let delay = self
+                .retry_policy
+                .as_ref()
+                .and_then(|policy| policy.backoff(attempts, idempotent, &err));
+
+            match (next, delay) {
+                (Some(next), Some(delay)) => {
+                    std::thread::sleep(delay);
+                    req = next;
+                    attempts += 1;
+                }
+                _ => return Err(err.with_attempts(attempts)),
             }
         }
-        headers
     }
+
+    fn execute_once<T: DeserializeOwned>(
+        req: RequestBuilder,
+    ) -> Result<T, Error> {
+        let res = req.send()?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        decode_response(status, &headers, &res.bytes()?)
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn construct_headers(api_key: &str) -> Result<HeaderMap, Error> {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static("application/json"));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.insert(
+        AUTHORIZATION,
+        HeaderValue::from_str(api_key)
+            .map_err(|_| Error::InvalidHeader(AUTHORIZATION.to_string()))?,
+    );
+
+    Ok(headers)
+}
+
+/// Percent-encodes all but unreserved characters, see RFC 3986.
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn encode_path_segment(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+
+    for b in segment.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(char::from(b));
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+
+    out
 }
 
-#[cfg(test)]
+/// Unwraps the `results` of a successful response or collects the `errors`
+/// of a failed one. An empty successful body, i.e. `204 No Content`, is
+/// decoded as `null`.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn decode_response<T: DeserializeOwned>(
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<T, Error> {
+    if status.is_success() {
+        if body.is_empty() {
+            return Ok(T::deserialize(Value::Null)?);
+        }
+
+        let Results { results } = serde_json::from_slice(body)?;
+        Ok(results)
+    } else {
+        let errors = serde_json::from_slice::<Errors>(body)
+            .map(|body| body.errors)
+            .unwrap_or_default();
+
+        Err(Error::Api {
+            status,
+            errors,
+            retry_after: retry_after(headers),
+        })
+    }
+}
+
+#[cfg(all(test, any(feature = "blocking", feature = "async")))]
 mod tests {
     use super::*;
+    #[cfg(feature = "blocking")]
+    use crate::transmission::testing::{get_api_key, message, SEND_RESULTS};
 
-    fn get_api_key() -> String {
-        use dotenvy::dotenv;
-        use std::env;
-        dotenv().ok();
-        env::var("SPARKPOST_API_KEY").expect("SPARKPOST_API_KEY must be set")
+    #[test]
+    fn decode_results() {
+        let body = br#"{
+            "results": {
+                "total_rejected_recipients": 0,
+                "total_accepted_recipients": 1,
+                "id": "11668787484950529"
+            }
+        }"#;
+
+        let res: ApiResponse =
+            decode_response(StatusCode::OK, &HeaderMap::new(), body).unwrap();
+        assert_eq!(res.id, "11668787484950529");
+        assert_eq!(res.total_accepted_recipients, 1);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let body = br#"{
+            "errors": [
+                {
+                    "message": "Invalid domain",
+                    "description": "Unconfigured or unverified sending domain.",
+                    "code": "7001"
+                }
+            ]
+        }"#;
+
+        let err = decode_response::<ApiResponse>(
+            StatusCode::BAD_REQUEST,
+            &HeaderMap::new(),
+            body,
+        )
+        .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(err.api_errors().len(), 1);
+        assert_eq!(err.api_errors()[0].code.as_deref(), Some("7001"));
+
+        // non-JSON error bodies still produce an API error
+        let err = decode_response::<ApiResponse>(
+            StatusCode::BAD_GATEWAY,
+            &HeaderMap::new(),
+            b"<html>Bad Gateway</html>",
+        )
+        .unwrap_err();
+        assert!(err.is_retryable());
+        assert!(err.api_errors().is_empty());
+    }
+
+    #[test]
+    fn decode_no_content() {
+        decode_response::<()>(StatusCode::NO_CONTENT, &HeaderMap::new(), b"")
+            .unwrap();
+    }
+
+    #[test]
+    fn invalid_api_key() {
+        let err = construct_headers("bad\nkey").unwrap_err();
+        assert!(matches!(err, Error::InvalidHeader(_)));
+    }
+
+    #[cfg(feature = "blocking")]
+    fn retrying_transmission(server: &mockito::Server) -> Transmission {
+        use std::time::Duration;
+
+        Transmission::builder("key")
+            .base_url(server.url())
+            .retry_policy(
+                RetryPolicy::new()
+                    .max_attempts(3)
+                    .base_delay(Duration::from_millis(1)),
+            )
+            .build()
+            .unwrap()
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn retry_rate_limited_send() {
+        let mut server = mockito::Server::new();
+        let limited = server
+            .mock("POST", "/transmissions")
+            .with_status(429)
+            .with_header("retry-after", "0")
+            .with_body(r#"{"errors":[{"message":"Too many requests"}]}"#)
+            .expect(2)
+            .create();
+        let accepted = server
+            .mock("POST", "/transmissions")
+            .with_body(SEND_RESULTS)
+            .expect(1)
+            .create();
+
+        let res = retrying_transmission(&server).send(&message()).unwrap();
+        assert_eq!(res.id, "11668787484950529");
+        limited.assert();
+        accepted.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn retry_gives_up_after_max_attempts() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/transmissions/1")
+            .with_status(503)
+            .expect(3)
+            .create();
+
+        let err = retrying_transmission(&server)
+            .scheduled_by_id("1")
+            .unwrap_err();
+        assert_eq!(err.attempts(), 3);
+        assert_eq!(err.status(), Some(StatusCode::SERVICE_UNAVAILABLE));
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn no_retry_for_unsafe_failures() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/transmissions")
+            .with_status(500)
+            .expect(1)
+            .create();
+
+        let err = retrying_transmission(&server).send(&message()).unwrap_err();
+        assert_eq!(err.attempts(), 1);
+        assert_eq!(err.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn rate_limited_across_threads() {
+        use std::{
+            sync::Arc,
+            thread,
+            time::{Duration, Instant},
+        };
+
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/transmissions")
+            .with_body(SEND_RESULTS)
+            .expect(3)
+            .create();
+
+        let tm = Arc::new(
+            Transmission::builder("key")
+                .base_url(server.url())
+                .rate_limiter(RateLimiter::new(20.0, 1))
+                .build()
+                .unwrap(),
+        );
+
+        let start = Instant::now();
+
+        let handles = (0..3)
+            .map(|_| {
+                let tm = Arc::clone(&tm);
+                thread::spawn(move || tm.send(&message()).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // first request uses the burst, the other two wait 50ms each
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        mock.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn list_query_parameters() {
+        use mockito::Matcher;
+
+        let mut server = mockito::Server::new();
+        let filtered = server
+            .mock("GET", "/transmissions")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("campaign_id".into(), "spring sale".into()),
+                Matcher::UrlEncoded("template_id".into(), "offer".into()),
+            ]))
+            .match_header("campaign_id", Matcher::Missing)
+            .match_header("template_id", Matcher::Missing)
+            .with_body(
+                r#"{"results":[{"id":"1","state":"submitted","campaign_id":"spring sale"}]}"#,
+            )
+            .create();
+
+        let tm = Transmission::builder("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        let query = ListTransmissionsQuery::new()
+            .campaign_id("spring sale")
+            .template_id("offer");
+        let list = tm.scheduled_transmissions(&query).unwrap();
+        assert_eq!(list[0].state, Some(TransmissionState::Submitted));
+        filtered.assert();
+
+        let unfiltered = server
+            .mock("GET", "/transmissions")
+            .match_query(Matcher::Exact(String::new()))
+            .with_body(r#"{"results":[]}"#)
+            .create();
+
+        tm.scheduled_transmissions(&ListTransmissionsQuery::default())
+            .unwrap();
+        unfiltered.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn delete() {
+        let mut server = mockito::Server::new();
+        let tm = Transmission::builder("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        let deleted = server
+            .mock("DELETE", "/transmissions/1")
+            .with_status(204)
+            .create();
+        tm.delete("1").unwrap();
+        deleted.assert();
+
+        let not_found = server
+            .mock("DELETE", "/transmissions/2")
+            .with_status(404)
+            .with_body(
+                r#"{"errors":[{"message":"resource not found","code":"1600"}]}"#,
+            )
+            .create();
+        let err = tm.delete("2").unwrap_err();
+        assert!(err.is_not_found());
+        assert!(!err.is_conflict());
+        not_found.assert();
+
+        let generating = server
+            .mock("DELETE", "/transmissions/3")
+            .with_status(409)
+            .with_body(
+                r#"{"errors":[{"message":"Transmission could not be deleted","description":"Transmission is generating","code":"2001"}]}"#,
+            )
+            .create();
+        let err = tm.delete("3").unwrap_err();
+        assert!(err.is_conflict());
+        assert!(!err.is_not_found());
+        generating.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn encoded_transmission_id() {
+        let mut server = mockito::Server::new();
+        let retrieved = server
+            .mock("GET", "/transmissions/x%3Fcampaign_id%3Dy%23z")
+            .with_body(r#"{"results":{"transmission":{"id":"1","state":"submitted"}}}"#)
+            .create();
+        let deleted = server
+            .mock("DELETE", "/transmissions/..%2Fx")
+            .with_status(204)
+            .create();
+
+        let tm = Transmission::builder("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        tm.scheduled_by_id("x?campaign_id=y#z").unwrap();
+        tm.delete("../x").unwrap();
+
+        retrieved.assert();
+        deleted.assert();
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn delete_by_campaign() {
+        use mockito::Matcher;
+
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("DELETE", "/transmissions")
+            .match_query(Matcher::UrlEncoded(
+                "campaign_id".into(),
+                "black friday".into(),
+            ))
+            .with_status(204)
+            .create();
+
+        let tm = Transmission::builder("key")
+            .base_url(server.url())
+            .build()
+            .unwrap();
+
+        tm.delete_by_campaign("black friday").unwrap();
+        mock.assert();
     }
 
     /// actually test the api
+    #[cfg(feature = "blocking")]
     #[ignore]
     #[test]
     fn send_email() {
@@ -222,21 +714,11 @@ mod tests {
             .html("This is the html body of the email")
             .text("This is the text body of the email");
 
-        //        println!("{:#?}", &email.json().to_string());
         let result = tm.send(&email);
-        //        println!("{:#?}", result);
         match result {
             Ok(res) => {
-                println!("{:?}", &res);
-                match res {
-                    TransmissionResponse::ApiResponse(api_res) => {
-                        assert_eq!(1, api_res.total_accepted_recipients);
-                        assert_eq!(0, api_res.total_rejected_recipients);
-                    }
-                    TransmissionResponse::ApiError(errors) => {
-                        println!("res: \n {:#?}", &errors);
-                    }
-                }
+                assert_eq!(1, res.total_accepted_recipients);
+                assert_eq!(0, res.total_rejected_recipients);
             }
             Err(error) => {
                 println!("error \n {:#?}", error);
@@ -249,19 +731,10 @@ mod tests {
             "iVBORw0KGgoAAAANSUhEUgAAABAAAAAQCAYAAAAf8/9hAAAAAXNSR0IArs4c6QAAAAlwSFlzAAAWJQAAFiUBSVIk8AAAAXxJREFUOBFjvJVg84P5718WBjLAX2bmPyxMf/+xMDH8YyZDPwPDXwYGJkIaOXTNGdiUtHAqI2jA/18/GUQzGsg3gMfKg4FVQo6BiYcPqyF4XcChaczA4+DP8P//f4b/P3+SZgAzvxCDSGYjAyMjI8PvZw+AoYXdLuyiQLtE0uoZWAREwLb+fnKXQTipkngXcJu7MnACQx8G2FX1GHgs3bDGBlYX8HlFM/z9+JbhzewWhmf1CQyfti9j+PfzBwO/ZxTMTDiNmQKBfmZX1GB42V/K8P38YbDCX/dvMDAwMzPwuYbBNcIYmC4AhfjvXwx/376AqQHTf96+ZPj34xuKGIiDaQBQ8PPBTQwCoZkMjJzcYA3MgqIMAr7xDJ/3rAHzkQnGO7FWf5gZ/qLmBSZmBoHgNAZee1+Gf18/MzCyczJ83LyQ4fPetch6Gf4xMP3FbgBMGdAgJqAr/n37zABMTTBROA0ygAWUJUG5Civ4B8xwX78CpbD6FJiHmf4AAFicbTMTr5jAAAAAAElFTkSuQmCC")).subject("Email with attachment");
 
         let result = tm.send(&email);
-        //        println!("{:#?}", result);
         match result {
             Ok(res) => {
-                println!("{:?}", &res);
-                match res {
-                    TransmissionResponse::ApiResponse(api_res) => {
-                        assert_eq!(1, api_res.total_accepted_recipients);
-                        assert_eq!(0, api_res.total_rejected_recipients);
-                    }
-                    TransmissionResponse::ApiError(errors) => {
-                        println!("res: \n {:#?}", &errors);
-                    }
-                }
+                assert_eq!(1, res.total_accepted_recipients);
+                assert_eq!(0, res.total_rejected_recipients);
             }
             Err(error) => {
                 println!("error \n {:#?}", error);
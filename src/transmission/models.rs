@@ -1,37 +1,142 @@
 use serde::{
     ser::{SerializeSeq, SerializeStruct, Serializer},
-    Serialize,
+    Deserialize, Deserializer, Serialize,
 };
 use serde_json::{to_value, Value};
-use std::convert::From;
+use std::{convert::From, fmt, str::FromStr};
+
+use crate::Error;
 
 /// Email Recipient
 /// Example
 /// ```rust
-/// extern crate sparkpost;
+/// extern crate sparklepost;
 ///
-/// use sparkpost::transmission::Recipient;
+/// use sparklepost::transmission::Recipient;
 ///
 /// let recipient = Recipient::from("test@test.com");
+///
+/// // with tags, metadata and return path
+/// let recipient = Recipient::from("test@test.com")
+///     .tag("welcome")
+///     .metadata(serde_json::json!({ "user_id": 42 }))
+///     .return_path("bounces@example.com");
 ///  ```
-#[derive(Debug, Serialize, Default, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
 pub struct Recipient {
     pub(crate) address: EmailAddress,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) return_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) metadata: Option<Value>,
     pub(crate) substitution_data: Option<Value>,
+    #[serde(skip)]
+    pub(crate) kind: RecipientKind,
+}
+
+/// How a recipient is addressed, CC and BCC recipients are modeled by
+/// SparkPost through `header_to` and the `CC` header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum RecipientKind {
+    #[default]
+    To,
+    Cc,
+    Bcc,
 }
 
 impl Recipient {
-    /// create recipient with substitute data for any type that implements Serialize from serde
+    /// create recipient with substitute data for any type that implements Serialize from serde,
+    /// panics where [`try_with_substitution`] fails
+    ///
+    /// [`try_with_substitution`]: Recipient::try_with_substitution
     pub fn with_substitution<T: Serialize>(
         address: EmailAddress,
         data: T,
     ) -> Self {
-        Recipient {
-            address,
-            substitution_data: Some(
-                to_value(data).expect("unable to serialize data"),
-            ),
-        }
+        Self::try_with_substitution(address, data)
+            .expect("unable to serialize data")
+    }
+
+    /// create recipient with substitute data, failing if `data` cannot be
+    /// serialized to JSON
+    pub fn try_with_substitution<T: Serialize>(
+        address: EmailAddress,
+        data: T,
+    ) -> Result<Self, Error> {
+        Ok(Recipient {
+            substitution_data: Some(to_value(data)?),
+            ..Recipient::from(address)
+        })
+    }
+
+    /// add a tag, shown in events for this recipient
+    pub fn tag<T: Into<String>>(mut self, tag: T) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// add tags, shown in events for this recipient
+    pub fn tags<I, T>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// set metadata, shown in events for this recipient and merged with
+    /// the transmission metadata, panics where [`try_metadata`] fails
+    ///
+    /// [`try_metadata`]: Recipient::try_metadata
+    pub fn metadata<T: Serialize>(self, data: T) -> Self {
+        self.try_metadata(data).expect("unable to serialize data")
+    }
+
+    /// set metadata, failing if `data` cannot be serialized to JSON
+    pub fn try_metadata<T: Serialize>(
+        mut self,
+        data: T,
+    ) -> Result<Self, Error> {
+        self.metadata = Some(to_value(data)?);
+        Ok(self)
+    }
+
+    /// set bounce address for this recipient, overriding the transmission
+    /// return path
+    ///
+    /// Only supported by SparkPost Enterprise.
+    pub fn return_path<T: Into<String>>(mut self, return_path: T) -> Self {
+        self.return_path = Some(return_path.into());
+        self
+    }
+
+    /// recipient address
+    pub fn address(&self) -> &EmailAddress {
+        &self.address
+    }
+
+    /// substitution data for this recipient
+    pub fn substitution_data(&self) -> Option<&Value> {
+        self.substitution_data.as_ref()
+    }
+
+    /// tags of this recipient, set with [`tag`](Recipient::tag)
+    pub fn get_tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// metadata of this recipient, set with [`metadata`](Recipient::metadata)
+    pub fn get_metadata(&self) -> Option<&Value> {
+        self.metadata.as_ref()
+    }
+
+    /// bounce address of this recipient, set with
+    /// [`return_path`](Recipient::return_path)
+    pub fn get_return_path(&self) -> Option<&str> {
+        self.return_path.as_deref()
     }
 }
 
@@ -43,10 +148,7 @@ impl<'a> From<&'a str> for Recipient {
 
 impl From<String> for Recipient {
     fn from(email: String) -> Self {
-        Recipient {
-            address: EmailAddress { email, name: None },
-            substitution_data: None,
-        }
+        EmailAddress::from(email).into()
     }
 }
 
@@ -54,11 +156,94 @@ impl From<EmailAddress> for Recipient {
     fn from(address: EmailAddress) -> Self {
         Recipient {
             address,
-            substitution_data: None,
+            ..Default::default()
         }
     }
 }
 
+/// How recipient addresses are compared when de-duplicating recipients of a
+/// [`Message`](super::Message)
+///
+/// Domains are always compared case-insensitively. Folding the case of the
+/// local part and stripping `+tag` suffixes are opt-in, since mailbox
+/// providers differ in how they treat them.
+///
+/// ```rust
+/// use sparklepost::transmission::{Message, Normalization};
+///
+/// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
+/// email
+///     .normalization(Normalization::new().fold_local_part(true))
+///     .add_recipient("Wilma@Example.com")
+///     .add_recipient("wilma@example.com");
+///
+/// assert_eq!(email.merged_recipients().len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Normalization {
+    fold_local_part: bool,
+    strip_plus_tag: bool,
+}
+
+impl Normalization {
+    /// creates new normalization comparing domains case-insensitively only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// compare the local part case-insensitively as well, i.e.
+    /// `Wilma@example.com` and `wilma@example.com`
+    pub fn fold_local_part(mut self, fold_local_part: bool) -> Self {
+        self.fold_local_part = fold_local_part;
+        self
+    }
+
+    /// ignore everything after a `+` in the local part, i.e.
+    /// `wilma+news@example.com` and `wilma@example.com`
+    pub fn strip_plus_tag(mut self, strip_plus_tag: bool) -> Self {
+        self.strip_plus_tag = strip_plus_tag;
+        self
+    }
+
+    /// Returns the form of `email` used for comparison.
+    pub fn normalize(&self, email: &str) -> String {
+        let (local, domain) = match email.trim().rsplit_once('@') {
+            Some(parts) => parts,
+            None => return email.trim().to_owned(),
+        };
+
+        // quoted local parts are compared as is
+        let mut local = if local.starts_with('"') {
+            local.to_owned()
+        } else {
+            let local = match local.split_once('+') {
+                Some((base, _)) if self.strip_plus_tag => base,
+                _ => local,
+            };
+
+            if self.fold_local_part {
+                local.to_lowercase()
+            } else {
+                local.to_owned()
+            }
+        };
+
+        local.push('@');
+        local.push_str(&domain.to_lowercase());
+        local
+    }
+}
+
+/// Recipient that was replaced by a later one with the same normalized
+/// address, see [`Message::merged_recipients`](super::Message::merged_recipients)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedRecipient {
+    /// address of the replaced recipient
+    pub replaced: String,
+    /// address of the recipient that replaced it
+    pub replacement: String,
+}
+
 #[derive(Debug)]
 pub enum RecipientSet {
     LocalList(Vec<Recipient>),
@@ -71,6 +256,25 @@ impl Default for RecipientSet {
     }
 }
 
+impl<'de> Deserialize<'de> for RecipientSet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Data {
+            LocalList(Vec<Recipient>),
+            ListName { list_id: String },
+        }
+
+        Ok(match Data::deserialize(deserializer)? {
+            Data::LocalList(list) => RecipientSet::LocalList(list),
+            Data::ListName { list_id } => RecipientSet::ListName(list_id),
+        })
+    }
+}
+
 impl Serialize for RecipientSet {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -97,18 +301,30 @@ impl Serialize for RecipientSet {
 ///
 /// ### Example
 /// ```rust
-/// use sparkpost::transmission::EmailAddress;
+/// use sparklepost::transmission::EmailAddress;
 ///
 /// let address = EmailAddress::from("test@test.com");
 ///
 /// // create address with name
 /// let address = EmailAddress::new("test@test.com", "Name");
 ///
+/// // parse and validate an address with display name
+/// let address: EmailAddress = "\"Doe, Jane\" <jane@example.com>".parse().unwrap();
+/// assert_eq!(address.email(), "jane@example.com");
+/// assert_eq!(address.name(), Some("Doe, Jane"));
+/// assert_eq!(address.to_string(), "\"Doe, Jane\" <jane@example.com>");
 ///```
-#[derive(Debug, Serialize, Default, PartialEq)]
+///
+/// Converting from a string with [`From`] takes it as email address as is,
+/// use [`parse`](EmailAddress::parse) to split off a display name and
+/// validate the address.
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
 pub struct EmailAddress {
     pub(crate) email: String,
     pub(crate) name: Option<String>,
+    /// `To` header shown to CC and BCC recipients
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) header_to: Option<String>,
 }
 
 impl EmailAddress {
@@ -117,22 +333,225 @@ impl EmailAddress {
         EmailAddress {
             email: email.into(),
             name: Some(name.into()),
+            header_to: None,
+        }
+    }
+
+    /// parse an address as written in email headers, i.e.
+    /// `jane@example.com`, `Jane Doe <jane@example.com>` or
+    /// `"Doe, Jane" <jane@example.com>`
+    ///
+    /// Domains may contain non-ASCII characters, they are kept as given.
+    pub fn parse(input: &str) -> Result<Self, ParseAddressError> {
+        let input = input.trim();
+        let err = |reason| ParseAddressError {
+            input: input.to_owned(),
+            reason,
+        };
+
+        let (name, email) = match input.strip_suffix('>') {
+            Some(rest) => {
+                let open = rest
+                    .rfind('<')
+                    .ok_or_else(|| err("missing opening `<`"))?;
+                let name =
+                    parse_display_name(rest[..open].trim()).map_err(err)?;
+                (name, &rest[open + 1..])
+            }
+            None => (None, input),
+        };
+
+        check_addr_spec(email).map_err(err)?;
+
+        Ok(EmailAddress {
+            email: email.to_owned(),
+            name,
+            header_to: None,
+        })
+    }
+
+    /// email address, i.e. `jane@example.com`
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    /// display name, if any
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl FromStr for EmailAddress {
+    type Err = ParseAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EmailAddress::parse(s)
+    }
+}
+
+/// Renders the address as used in email headers, quoting the name if needed.
+///
+/// Control characters, i.e. line breaks, are dropped so the rendered address
+/// cannot start another header.
+impl fmt::Display for EmailAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let strip = |s: &str| s.chars().filter(|c| !c.is_control()).collect();
+        let email: String = strip(&self.email);
+        let name: String = strip(self.name.as_deref().unwrap_or_default());
+
+        if name.is_empty() {
+            return f.write_str(&email);
+        }
+
+        if name
+            .split(' ')
+            .all(|word| !word.is_empty() && word.chars().all(is_phrase_char))
+        {
+            write!(f, "{name} <{email}>")
+        } else {
+            f.write_str("\"")?;
+            for c in name.chars() {
+                if matches!(c, '"' | '\\') {
+                    f.write_str("\\")?;
+                }
+                write!(f, "{c}")?;
+            }
+            write!(f, "\" <{email}>")
         }
     }
 }
 
+/// Error returned when parsing an [`EmailAddress`] fails
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAddressError {
+    input: String,
+    reason: &'static str,
+}
+
+impl fmt::Display for ParseAddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid email address `{}`: {}", self.input, self.reason)
+    }
+}
+
+impl std::error::Error for ParseAddressError {}
+
+/// Characters allowed in an atom, see RFC 5322 and RFC 6532.
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || "!#$%&'*+-/=?^_`{|}~".contains(c)
+        || !c.is_ascii()
+}
+
+/// Characters allowed in an unquoted display name, which may contain dots
+/// as is common practice.
+fn is_phrase_char(c: char) -> bool {
+    is_atext(c) || c == '.'
+}
+
+fn parse_display_name(name: &str) -> Result<Option<String>, &'static str> {
+    if name.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(quoted) = name.strip_prefix('"') {
+        let quoted = quoted
+            .strip_suffix('"')
+            .ok_or("unterminated quoted display name")?;
+        return unquote(quoted).map(Some);
+    }
+
+    if !name.chars().all(|c| is_phrase_char(c) || c == ' ') {
+        return Err(
+            "display name contains special characters and is not quoted",
+        );
+    }
+
+    Ok(Some(name.split_whitespace().collect::<Vec<_>>().join(" ")))
+}
+
+/// Resolves backslash escapes of a quoted string without its quotes.
+fn unquote(quoted: &str) -> Result<String, &'static str> {
+    let mut out = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.push(chars.next().ok_or("unterminated escape")?),
+            '"' => return Err("unescaped quote in quoted string"),
+            '\r' | '\n' => return Err("line break in quoted string"),
+            c => out.push(c),
+        }
+    }
+
+    Ok(out)
+}
+
+fn check_addr_spec(email: &str) -> Result<(), &'static str> {
+    let (local, domain) = email.rsplit_once('@').ok_or("missing `@`")?;
+
+    if local.is_empty() {
+        return Err("empty local part");
+    }
+
+    if local.len() > 64 {
+        return Err("local part longer than 64 characters");
+    }
+
+    match local.strip_prefix('"') {
+        Some(quoted) => {
+            unquote(
+                quoted
+                    .strip_suffix('"')
+                    .ok_or("unterminated quoted local part")?,
+            )?;
+        }
+        None => {
+            if !local
+                .split('.')
+                .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+            {
+                return Err("invalid local part");
+            }
+        }
+    }
+
+    if domain.is_empty() {
+        return Err("empty domain");
+    }
+
+    if domain.len() > 253 {
+        return Err("domain longer than 253 characters");
+    }
+
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.chars().count() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    };
+
+    if !domain.split('.').all(valid_label) {
+        return Err("invalid domain");
+    }
+
+    Ok(())
+}
+
 impl<'a> From<&'a str> for EmailAddress {
     fn from(email: &'a str) -> Self {
-        EmailAddress {
-            email: email.to_owned(),
-            name: None,
-        }
+        email.to_owned().into()
     }
 }
 
 impl From<String> for EmailAddress {
     fn from(email: String) -> Self {
-        EmailAddress { email, name: None }
+        EmailAddress {
+            email,
+            name: None,
+            header_to: None,
+        }
     }
 }
 
@@ -157,6 +576,7 @@ mod test {
         EmailAddress {
             email: "test@test.com".into(),
             name: Some("Name".into()),
+            header_to: None,
         }
     }
 
@@ -180,6 +600,7 @@ mod test {
         let address = EmailAddress {
             email: "test@test.com".into(),
             name: Some("Name".into()),
+            header_to: None,
         };
 
         assert_eq!(address, create_address())
@@ -214,12 +635,238 @@ mod test {
         let recipient = Recipient {
             address: create_address(),
             substitution_data: Some(to_value(data).unwrap()),
+            ..Default::default()
         };
         let string_value = "{\"address\":{\"email\":\"test@test.com\",\"name\":\"Name\"},\"substitution_data\":{\"name\":\"Name\"}}".to_owned();
         assert_eq!(string_value, to_value(&recipient).unwrap().to_string());
         assert_eq!(recipient, create_recipient());
     }
 
+    #[test]
+    fn recipient_try_with_substitution() {
+        use std::collections::HashMap;
+
+        let recipient = Recipient::try_with_substitution(
+            create_address(),
+            Data {
+                name: "Name".to_owned(),
+            },
+        )
+        .unwrap();
+        assert_eq!(recipient, create_recipient());
+
+        let mut bad_data = HashMap::new();
+        bad_data.insert(vec![1], "value");
+
+        let err = Recipient::try_with_substitution(create_address(), bad_data)
+            .unwrap_err();
+        assert!(matches!(err, Error::Serialization(_)));
+    }
+
+    #[test]
+    fn recipient_tags_metadata_return_path() {
+        let recipient = Recipient::from("test@test.com")
+            .tag("welcome")
+            .tags(["onboarding", "trial"])
+            .metadata(Data {
+                name: "user_42".to_owned(),
+            })
+            .return_path("bounces@example.com");
+
+        assert_eq!(
+            to_value(&recipient).unwrap(),
+            serde_json::json!({
+                "address": { "email": "test@test.com", "name": null },
+                "return_path": "bounces@example.com",
+                "tags": ["welcome", "onboarding", "trial"],
+                "metadata": { "name": "user_42" },
+                "substitution_data": null
+            })
+        );
+
+        let mut bad_data = std::collections::HashMap::new();
+        bad_data.insert(vec![1], "value");
+
+        let err = Recipient::from("test@test.com")
+            .try_metadata(bad_data)
+            .unwrap_err();
+        assert!(matches!(err, Error::Serialization(_)));
+    }
+
+    #[test]
+    fn deserialize_recipients() {
+        let json = r#"[
+            {
+                "address": {
+                    "email": "to@example.com",
+                    "name": "To",
+                    "header_to": "other@example.com"
+                },
+                "return_path": "bounces@example.com",
+                "tags": ["welcome"],
+                "metadata": { "user_id": 42 },
+                "substitution_data": { "name": "Name" }
+            },
+            { "address": { "email": "plain@example.com" } }
+        ]"#;
+
+        let set: RecipientSet = serde_json::from_str(json).unwrap();
+        let list = match set {
+            RecipientSet::LocalList(ref list) => list,
+            RecipientSet::ListName(_) => unreachable!(),
+        };
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(
+            list[0].address.header_to.as_deref(),
+            Some("other@example.com")
+        );
+        assert_eq!(list[0].tags, ["welcome"]);
+        assert_eq!(list[1], Recipient::from("plain@example.com"));
+
+        // serializing again restores omitted optional fields as null
+        let value = to_value(&set).unwrap();
+        let again: RecipientSet =
+            serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(to_value(&again).unwrap(), value);
+
+        let set: RecipientSet =
+            serde_json::from_str(r#"{ "list_id": "my_list" }"#).unwrap();
+        assert!(
+            matches!(set, RecipientSet::ListName(ref name) if name == "my_list")
+        );
+    }
+
+    #[test]
+    fn parse_address() {
+        let parse = |input: &str| EmailAddress::parse(input).unwrap();
+
+        assert_eq!(parse(" jane@example.com "), "jane@example.com".into());
+        assert_eq!(
+            parse("Jane  Doe <jane@example.com>"),
+            EmailAddress::new("jane@example.com", "Jane Doe")
+        );
+        assert_eq!(
+            parse(r#""Doe, \"Jane\"" <jane@example.com>"#),
+            EmailAddress::new("jane@example.com", r#"Doe, "Jane""#)
+        );
+        assert_eq!(parse("<jane@example.com>"), "jane@example.com".into());
+        assert_eq!(
+            parse("J. Doe <jane.doe+news@example.com>"),
+            EmailAddress::new("jane.doe+news@example.com", "J. Doe")
+        );
+        assert_eq!(
+            parse(r#""jane doe"@example.com"#).email,
+            r#""jane doe"@example.com"#
+        );
+        assert_eq!(parse("jane@bücher.example").email, "jane@bücher.example");
+
+        let address: EmailAddress = "Jane <jane@example.com>".parse().unwrap();
+        assert_eq!(address.email(), "jane@example.com");
+        assert_eq!(address.name(), Some("Jane"));
+    }
+
+    #[test]
+    fn parse_invalid_address() {
+        for input in [
+            "",
+            "jane",
+            "@example.com",
+            "jane@",
+            "jane@@example.com",
+            "jane..doe@example.com",
+            ".jane@example.com",
+            "jane@example..com",
+            "jane@-example.com",
+            "jane@exa_mple.com",
+            "Jane <jane@example.com",
+            "Jane jane@example.com>",
+            "Doe, Jane <jane@example.com>",
+            r#""Jane <jane@example.com>"#,
+            "Jane <jane@example.com> <other@example.com>",
+        ] {
+            assert!(
+                EmailAddress::parse(input).is_err(),
+                "`{input}` should be rejected"
+            );
+        }
+
+        let err = EmailAddress::parse("jane").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid email address `jane`: missing `@`"
+        );
+    }
+
+    #[test]
+    fn display_address() {
+        assert_eq!(
+            EmailAddress::from("jane@example.com").to_string(),
+            "jane@example.com"
+        );
+        assert_eq!(
+            EmailAddress::new("jane@example.com", "Jane Doe").to_string(),
+            "Jane Doe <jane@example.com>"
+        );
+        assert_eq!(
+            EmailAddress::new("jane@example.com", r#"Doe, "Jane""#).to_string(),
+            r#""Doe, \"Jane\"" <jane@example.com>"#
+        );
+        assert_eq!(
+            EmailAddress::new("jane@example.com", "").to_string(),
+            "jane@example.com"
+        );
+
+        // line breaks cannot inject headers
+        let evil = EmailAddress::new("a@example.com", "Evil\r\nBcc: x@y.z");
+        assert_eq!(evil.to_string(), r#""EvilBcc: x@y.z" <a@example.com>"#);
+        assert_eq!(
+            EmailAddress::parse(&evil.to_string()).unwrap(),
+            EmailAddress::new("a@example.com", "EvilBcc: x@y.z")
+        );
+        assert_eq!(
+            EmailAddress::from("a@example.com\nBcc: x@y.z").to_string(),
+            "a@example.comBcc: x@y.z"
+        );
+
+        // rendered form parses back to the same address
+        for address in [
+            EmailAddress::new("jane@example.com", "J. Doe"),
+            EmailAddress::new("jane@example.com", r#"Doe, "Jane" \ Co"#),
+        ] {
+            assert_eq!(
+                EmailAddress::parse(&address.to_string()).unwrap(),
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn normalize() {
+        let default = Normalization::new();
+        assert_eq!(
+            default.normalize("Wilma+News@Example.COM"),
+            "Wilma+News@example.com"
+        );
+
+        let folded = Normalization::new().fold_local_part(true);
+        assert_eq!(
+            folded.normalize(" Wilma+News@Example.COM"),
+            "wilma+news@example.com"
+        );
+
+        let stripped = folded.strip_plus_tag(true);
+        assert_eq!(
+            stripped.normalize("Wilma+News@Example.COM"),
+            "wilma@example.com"
+        );
+        assert_eq!(
+            stripped.normalize(r#""Wilma+News"@Example.COM"#),
+            r#""Wilma+News"@example.com"#
+        );
+        assert_eq!(stripped.normalize("not-an-address"), "not-an-address");
+    }
+
     #[test]
     fn recipient_from_str() {
         let recipient: Recipient = "test@test.com".into();
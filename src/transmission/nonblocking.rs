@@ -0,0 +1,257 @@
+use reqwest::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+
+use super::{
+    construct_headers, decode_response, encode_path_segment,
+    response::TransmissionWrapper, ApiResponse, ListTransmissionsQuery,
+    Message, TransmissionBuilder, TransmissionDetails, TransmissionSummary,
+};
+use crate::{Error, RateLimiter, RetryPolicy};
+
+/// Sparkpost Transmission, using an async HTTP client
+///
+/// Mirrors the API of [`Transmission`](super::Transmission) but every request
+/// method is an `async fn`.
+///
+/// ```
+/// use sparklepost::transmission::AsyncTransmission;
+/// let tm = AsyncTransmission::new("api_key_form_env".to_string());
+/// ```
+///
+/// Use [`AsyncTransmission::builder`] to configure the base URL, timeouts,
+/// proxy or HTTP client.
+///
+/// For more info see <https://developers.sparkpost.com/api/transmissions>.
+#[derive(Debug)]
+pub struct AsyncTransmission {
+    pub(crate) api_key: String,
+    pub(crate) base_url: String,
+    pub(crate) url: String,
+    pub(crate) client: Client,
+    pub(crate) retry_policy: Option<RetryPolicy>,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) validate: bool,
+}
+
+impl AsyncTransmission {
+    /// creates new AsyncTransmission with api key for global version
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, see
+    /// [`reqwest::Client::new`].
+    pub fn new<T: Into<String>>(api_key: T) -> Self {
+        Self::builder(api_key)
+            .build_async()
+            .expect("HTTP client cannot be initialized")
+    }
+
+    /// creates new AsyncTransmission with api key for EU version
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be initialized, see
+    /// [`reqwest::Client::new`].
+    pub fn new_eu<T: Into<String>>(api_key: T) -> Self {
+        Self::builder(api_key)
+            .eu()
+            .build_async()
+            .expect("HTTP client cannot be initialized")
+    }
+
+    /// creates new [`TransmissionBuilder`] with api key
+    pub fn builder<T: Into<String>>(api_key: T) -> TransmissionBuilder {
+        TransmissionBuilder::new(api_key)
+    }
+
+    /// Send api request
+    pub async fn send(&self, message: &Message) -> Result<ApiResponse, Error> {
+        let req = self
+            .client
+            .post(&self.url)
+            .headers(construct_headers(&self.api_key)?)
+            .body(message.to_request_body(self.validate)?);
+
+        self.execute(req, false).await
+    }
+
+    /// Retrieve a Scheduled Transmission from API
+    pub async fn scheduled_by_id(
+        &self,
+        transmission_id: &str,
+    ) -> Result<TransmissionDetails, Error> {
+        let url =
+            format!("{}/{}", self.url, encode_path_segment(transmission_id));
+
+        let req = self
+            .client
+            .get(url)
+            .headers(construct_headers(&self.api_key)?);
+
+        self.execute(req, true)
+            .await
+            .map(|TransmissionWrapper { transmission }| transmission)
+    }
+
+    /// Retrieve all Scheduled Transmissions from API
+    ///
+    /// See [`Transmission::scheduled_transmissions`](super::Transmission::scheduled_transmissions).
+    pub async fn scheduled_transmissions(
+        &self,
+        query: &ListTransmissionsQuery,
+    ) -> Result<Vec<TransmissionSummary>, Error> {
+        let req = self
+            .client
+            .get(&self.url)
+            .headers(construct_headers(&self.api_key)?)
+            .query(query);
+
+        self.execute(req, true).await
+    }
+
+    /// Delete a scheduled transmission that has not started generating
+    ///
+    /// See [`Transmission::delete`](super::Transmission::delete).
+    pub async fn delete(&self, transmission_id: &str) -> Result<(), Error> {
+        let url =
+            format!("{}/{}", self.url, encode_path_segment(transmission_id));
+
+        let req = self
+            .client
+            .delete(url)
+            .headers(construct_headers(&self.api_key)?);
+
+        self.execute(req, true).await
+    }
+
+    /// Delete all scheduled transmissions of a campaign that have not started
+    /// generating
+    ///
+    /// See [`Transmission::delete_by_campaign`](super::Transmission::delete_by_campaign).
+    pub async fn delete_by_campaign(
+        &self,
+        campaign_id: &str,
+    ) -> Result<(), Error> {
+        let req = self
+            .client
+            .delete(&self.url)
+            .headers(construct_headers(&self.api_key)?)
+            .query(&[("campaign_id", campaign_id)]);
+
+        self.execute(req, true).await
+    }
+
+    /// Sends request, retrying according to the retry policy and waiting for
+    /// the rate limiter before every attempt.
+    pub(crate) async fn execute<T: DeserializeOwned>(
+        &self,
+        mut req: RequestBuilder,
+        idempotent: bool,
+    ) -> Result<T, Error> {
+        let mut attempts = 1;
+
+        loop {
+            let next = req.try_clone();
+
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.acquire_async().await;
+            }
+
+            let err = match Self::execute_once(req).await {
+                Ok(res) => return Ok(res),
+                Err(err) => err,
+            };
+
+            let delay = self
+                .retry_policy
+                .as_ref()
+                .and_then(|policy| policy.backoff(attempts, idempotent, &err));
+
+            match (next, delay) {
+                (Some(next), Some(delay)) => {
+                    tokio::time::sleep(delay).await;
+                    req = next;
+                    attempts += 1;
+                }
+                _ => return Err(err.with_attempts(attempts)),
+            }
+        }
+    }
+
+    async fn execute_once<T: DeserializeOwned>(
+        req: RequestBuilder,
+    ) -> Result<T, Error> {
+        let res = req.send().await?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        decode_response(status, &headers, &res.bytes().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transmission::{
+        testing::{get_api_key, message, SEND_RESULTS},
+        EmailAddress,
+    };
+
+    #[tokio::test]
+    async fn retry_rate_limited_send() {
+        use std::time::Duration;
+
+        let mut server = mockito::Server::new_async().await;
+        let limited = server
+            .mock("POST", "/transmissions")
+            .with_status(429)
+            .with_header("retry-after", "0")
+            .expect(1)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("POST", "/transmissions")
+            .with_body(SEND_RESULTS)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let tm = AsyncTransmission::builder("key")
+            .base_url(server.url())
+            .retry_policy(
+                RetryPolicy::new().base_delay(Duration::from_millis(1)),
+            )
+            .build_async()
+            .unwrap();
+
+        let res = tm.send(&message()).await.unwrap();
+        assert_eq!(res.id, "11668787484950529");
+        limited.assert_async().await;
+        accepted.assert_async().await;
+    }
+
+    /// actually test the api
+    #[ignore]
+    #[tokio::test]
+    async fn send_email() {
+        let tm = AsyncTransmission::new_eu(get_api_key());
+        let mut email: Message = Message::new(EmailAddress::new(
+            "hello@email.letsorganise.app",
+            "noreply",
+        ));
+        email
+            .add_recipient("test@hgill.io")
+            .subject("Testing async builder email sandbox")
+            .html("This is the html body of the email")
+            .text("This is the text body of the email");
+
+        match tm.send(&email).await {
+            Ok(res) => {
+                assert_eq!(1, res.total_accepted_recipients);
+                assert_eq!(0, res.total_rejected_recipients);
+            }
+            Err(error) => {
+                println!("error \n {:#?}", error);
+            }
+        }
+    }
+}
@@ -0,0 +1,1202 @@
+use serde_json::{Map, Number, Value};
+use std::{cmp::Ordering, fmt};
+
+use super::{
+    message::{Content, Message},
+    models::Recipient,
+};
+
+/// Maximum nesting of `render_dynamic_content` calls
+const MAX_DEPTH: usize = 10;
+
+/// Maximum nesting of `if` and `each` blocks, and of parentheses, `not` and
+/// function calls within an expression
+const MAX_NESTING: usize = 100;
+
+/// Content of a [`Message`] as rendered for one recipient, see
+/// [`Message::render`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedContent {
+    pub subject: String,
+    pub text: Option<String>,
+    pub html: Option<String>,
+    pub amp_html: Option<String>,
+}
+
+/// Error returned when a template cannot be rendered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderError {
+    reason: String,
+}
+
+impl RenderError {
+    fn new<T: Into<String>>(reason: T) -> Self {
+        RenderError {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cannot render template: {}", self.reason)
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl Message {
+    /// Renders subject and bodies for `recipient` like the API would,
+    /// without sending anything.
+    ///
+    /// Substitution data is taken from the message and recipient metadata,
+    /// message substitution data and recipient substitution data, later ones
+    /// overriding top-level keys of earlier ones. The recipient address is
+    /// available as `address.email` and `address.name`.
+    ///
+    /// Supported are variables, `or` defaults, `if`/`elseif`/`else`, `each`
+    /// loops with `loop_var` and `loop_index`, comparisons, `and`, `or`,
+    /// `not`, `empty()`, `render_dynamic_content()` and unescaped
+    /// triple-brace output, see
+    /// <https://developers.sparkpost.com/api/template-language/>. Only inline
+    /// content can be rendered.
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::{EmailAddress, Message, Recipient};
+    /// use serde_json::json;
+    ///
+    /// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
+    /// email
+    ///     .subject("Hello {{first_name or 'you great person'}}")
+    ///     .html("{{if vip}}<b>VIP</b> {{end}}{{each items}}{{loop_var}};{{end}}")
+    ///     .substitution_data(json!({ "items": ["<a>", "b"] }));
+    ///
+    /// let recipient = Recipient::with_substitution(
+    ///     EmailAddress::from("wilma@example.com"),
+    ///     json!({ "first_name": "Wilma", "vip": true }),
+    /// );
+    ///
+    /// let rendered = email.render(&recipient).unwrap();
+    /// assert_eq!(rendered.subject, "Hello Wilma");
+    /// assert_eq!(rendered.html.unwrap(), "<b>VIP</b> &lt;a&gt;;b;");
+    /// ```
+    pub fn render(
+        &self,
+        recipient: &Recipient,
+    ) -> Result<RenderedContent, RenderError> {
+        let content = match self.content {
+            Content::Inline(ref content) => content,
+            Content::Template { .. } => {
+                return Err(RenderError::new(
+                    "stored templates can only be rendered by the API",
+                ))
+            }
+            Content::Rfc822 { .. } => {
+                return Err(RenderError::new(
+                    "RFC 822 content cannot be rendered",
+                ))
+            }
+        };
+
+        let data = self.substitution_scope(recipient);
+        let perform = self.options.perform_substitutions != Some(false);
+
+        let render = |template: &str, html: bool| {
+            if perform {
+                render_template(template, &data, html)
+            } else {
+                Ok(template.to_owned())
+            }
+        };
+
+        Ok(RenderedContent {
+            subject: render(&content.subject, false)?,
+            text: content
+                .text
+                .as_deref()
+                .map(|text| render(text, false))
+                .transpose()?,
+            html: content
+                .html
+                .as_deref()
+                .map(|html| render(html, true))
+                .transpose()?,
+            amp_html: content
+                .amp_html
+                .as_deref()
+                .map(|amp_html| render(amp_html, true))
+                .transpose()?,
+        })
+    }
+
+    /// Merges metadata and substitution data of message and recipient.
+    fn substitution_scope(&self, recipient: &Recipient) -> Value {
+        let mut scope = Map::new();
+
+        for data in [
+            &self.metadata,
+            &recipient.metadata,
+            &self.substitution_data,
+            &recipient.substitution_data,
+        ] {
+            if let Some(Value::Object(map)) = data {
+                scope.extend(map.clone());
+            }
+        }
+
+        let mut address = Map::new();
+        address.insert(
+            "email".to_owned(),
+            Value::String(recipient.address.email.clone()),
+        );
+        if let Some(ref name) = recipient.address.name {
+            address.insert("name".to_owned(), Value::String(name.clone()));
+        }
+        scope.insert("address".to_owned(), Value::Object(address));
+
+        Value::Object(scope)
+    }
+}
+
+/// Renders a template in the SparkPost templating language with `data`.
+///
+/// Double-brace output is HTML-escaped if `escape_html` is set, as for HTML
+/// and AMP HTML bodies. See [`Message::render`] for the supported syntax.
+///
+/// ```rust
+/// use sparklepost::transmission::render_template;
+/// use serde_json::json;
+///
+/// let text = render_template(
+///     "Hi {{name or 'there'}}!",
+///     &json!({ "name": "Fred" }),
+///     false,
+/// )
+/// .unwrap();
+/// assert_eq!(text, "Hi Fred!");
+/// ```
+pub fn render_template(
+    template: &str,
+    data: &Value,
+    escape_html: bool,
+) -> Result<String, RenderError> {
+    let nodes = parse(template)?;
+
+    let mut renderer = Renderer {
+        data,
+        loops: Vec::new(),
+        escape_html,
+        depth: 0,
+    };
+
+    let mut out = String::with_capacity(template.len());
+    renderer.render(&nodes, &mut out)?;
+    Ok(out)
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Output {
+        expr: Expr,
+        escape: bool,
+    },
+    If {
+        branches: Vec<(Expr, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    Each {
+        list: Expr,
+        body: Vec<Node>,
+    },
+}
+
+#[derive(Debug)]
+enum Tag {
+    Output { expr: Expr, escape: bool },
+    If(Expr),
+    ElseIf(Expr),
+    Else,
+    End,
+    Each(Expr),
+}
+
+#[derive(Debug)]
+enum Item {
+    Text(String),
+    Tag(Tag),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value),
+    Path(Vec<Segment>),
+    Not(Box<Expr>),
+    /// operands of a chain of `and`, evaluated until one is falsy
+    And(Vec<Expr>),
+    /// operands of a chain of `or`, evaluated until one is truthy
+    Or(Vec<Expr>),
+    Compare(Box<Expr>, &'static str, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, RenderError> {
+    let mut items = lex(template)?.into_iter();
+
+    match parse_block(&mut items, 0)? {
+        (nodes, None) => Ok(nodes),
+        (_, Some(tag)) => Err(RenderError::new(format!(
+            "`{}` without matching `if` or `each`",
+            tag_name(&tag)
+        ))),
+    }
+}
+
+fn tag_name(tag: &Tag) -> &'static str {
+    match tag {
+        Tag::ElseIf(_) => "elseif",
+        Tag::Else => "else",
+        _ => "end",
+    }
+}
+
+/// Splits the template into text and tags.
+fn lex(template: &str) -> Result<Vec<Item>, RenderError> {
+    let mut items = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            items.push(Item::Text(rest[..start].to_owned()));
+        }
+
+        let tag = &rest[start..];
+        let (inner, escape, len) = match tag.strip_prefix("{{{") {
+            Some(body) => {
+                let end = find_close(body, "}}}").ok_or_else(|| {
+                    RenderError::new("unterminated `{{{` tag")
+                })?;
+                (&body[..end], false, end + 6)
+            }
+            None => {
+                let end = find_close(&tag[2..], "}}")
+                    .ok_or_else(|| RenderError::new("unterminated `{{` tag"))?;
+                (&tag[2..end + 2], true, end + 4)
+            }
+        };
+
+        items.push(Item::Tag(parse_tag(inner, escape)?));
+        rest = &tag[len..];
+    }
+
+    if !rest.is_empty() {
+        items.push(Item::Text(rest.to_owned()));
+    }
+
+    Ok(items)
+}
+
+/// Finds the closing braces of a tag, skipping braces in string literals.
+fn find_close(tag: &str, close: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut chars = tag.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(_) if c == '\\' => {
+                chars.next();
+            }
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if matches!(c, '\'' | '"') => quote = Some(c),
+            None if tag[i..].starts_with(close) => return Some(i),
+            None => {}
+        }
+    }
+
+    None
+}
+
+fn parse_tag(inner: &str, escape: bool) -> Result<Tag, RenderError> {
+    let tokens = tokenize(inner)?;
+
+    let keyword = match tokens.first() {
+        Some(Token::Ident(ident)) => ident.as_str(),
+        Some(_) => "",
+        None => return Err(RenderError::new("empty tag")),
+    };
+
+    let tag = match keyword {
+        "if" => Tag::If(parse_expr(&tokens[1..])?),
+        "elseif" => Tag::ElseIf(parse_expr(&tokens[1..])?),
+        "each" => Tag::Each(parse_expr(&tokens[1..])?),
+        "else" | "end" if tokens.len() > 1 => {
+            return Err(RenderError::new(format!(
+                "unexpected tokens after `{keyword}`"
+            )))
+        }
+        "else" => Tag::Else,
+        "end" => Tag::End,
+        _ => Tag::Output {
+            expr: parse_expr(&tokens)?,
+            escape,
+        },
+    };
+
+    Ok(tag)
+}
+
+/// Parses nodes up to a closing `elseif`, `else` or `end` tag, which is
+/// returned as well. `depth` is the number of enclosing blocks.
+fn parse_block(
+    items: &mut impl Iterator<Item = Item>,
+    depth: usize,
+) -> Result<(Vec<Node>, Option<Tag>), RenderError> {
+    let mut nodes = Vec::new();
+
+    while let Some(item) = items.next() {
+        let tag = match item {
+            Item::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Item::Tag(tag) => tag,
+        };
+
+        if matches!(tag, Tag::If(_) | Tag::Each(_)) && depth >= MAX_NESTING {
+            return Err(RenderError::new(format!(
+                "`if` and `each` blocks nested deeper than {MAX_NESTING} levels"
+            )));
+        }
+
+        match tag {
+            Tag::Output { expr, escape } => {
+                nodes.push(Node::Output { expr, escape })
+            }
+            Tag::If(mut cond) => {
+                let mut branches = Vec::new();
+                let mut otherwise = Vec::new();
+
+                loop {
+                    let (body, end) = parse_block(items, depth + 1)?;
+                    branches.push((cond, body));
+
+                    match end {
+                        Some(Tag::ElseIf(next)) => cond = next,
+                        Some(Tag::Else) => {
+                            let (body, end) = parse_block(items, depth + 1)?;
+                            if !matches!(end, Some(Tag::End)) {
+                                return Err(RenderError::new(
+                                    "`else` without matching `end`",
+                                ));
+                            }
+                            otherwise = body;
+                            break;
+                        }
+                        Some(Tag::End) => break,
+                        _ => {
+                            return Err(RenderError::new(
+                                "`if` without matching `end`",
+                            ))
+                        }
+                    }
+                }
+
+                nodes.push(Node::If {
+                    branches,
+                    otherwise,
+                });
+            }
+            Tag::Each(list) => {
+                let (body, end) = parse_block(items, depth + 1)?;
+                if !matches!(end, Some(Tag::End)) {
+                    return Err(RenderError::new(
+                        "`each` without matching `end`",
+                    ));
+                }
+                nodes.push(Node::Each { list, body });
+            }
+            tag @ (Tag::ElseIf(_) | Tag::Else | Tag::End) => {
+                return Ok((nodes, Some(tag)))
+            }
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// variable path, function name or keyword
+    Ident(String),
+    Str(String),
+    Num(Number),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, RenderError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let token = match c {
+            '\'' | '"' => {
+                let mut s = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        Some('\\') => {
+                            i += 1;
+                            s.push(*chars.get(i).ok_or_else(|| {
+                                RenderError::new("unterminated string")
+                            })?);
+                        }
+                        Some(&q) if q == c => break,
+                        Some(&ch) => s.push(ch),
+                        None => {
+                            return Err(RenderError::new("unterminated string"))
+                        }
+                    }
+                    i += 1;
+                }
+                i += 1;
+                Token::Str(s)
+            }
+            '(' => {
+                i += 1;
+                Token::LParen
+            }
+            ')' => {
+                i += 1;
+                Token::RParen
+            }
+            ',' => {
+                i += 1;
+                Token::Comma
+            }
+            '=' | '!' | '<' | '>' => {
+                let eq = chars.get(i + 1) == Some(&'=');
+                i += if eq { 2 } else { 1 };
+                Token::Op(match (c, eq) {
+                    ('=', true) => "==",
+                    ('!', true) => "!=",
+                    ('<', false) => "<",
+                    ('<', true) => "<=",
+                    ('>', false) => ">",
+                    ('>', true) => ">=",
+                    _ => {
+                        return Err(RenderError::new(format!(
+                            "unexpected `{c}`"
+                        )))
+                    }
+                })
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_digit() || chars[i] == '.')
+                {
+                    i += 1;
+                }
+                let num: String = chars[start..i].iter().collect();
+                // keeps integers as such, so they are not rendered as `1.0`
+                Token::Num(serde_json::from_str(&num).map_err(|_| {
+                    RenderError::new(format!("invalid number `{num}`"))
+                })?)
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric()
+                        || matches!(chars[i], '_' | '.' | '[' | ']'))
+                {
+                    i += 1;
+                }
+                Token::Ident(chars[start..i].iter().collect())
+            }
+            c => return Err(RenderError::new(format!("unexpected `{c}`"))),
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn parse_expr(tokens: &[Token]) -> Result<Expr, RenderError> {
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.or()?;
+
+    match parser.tokens.get(parser.pos) {
+        None => Ok(expr),
+        Some(token) => Err(RenderError::new(format!(
+            "unexpected {token:?} in expression"
+        ))),
+    }
+}
+
+/// Recursive descent parser for expressions, from lowest to highest
+/// precedence: `or`, `and`, `not`, comparisons
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    /// number of enclosing parentheses, `not` and function calls
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword)
+        {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Counts a nested expression, failing beyond [`MAX_NESTING`] levels.
+    fn enter(&mut self) -> Result<(), RenderError> {
+        if self.depth >= MAX_NESTING {
+            return Err(RenderError::new(format!(
+                "expression nested deeper than {MAX_NESTING} levels"
+            )));
+        }
+
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<Expr, RenderError> {
+        self.enter()?;
+
+        let mut exprs = vec![self.and()?];
+        while self.keyword("or") {
+            exprs.push(self.and()?);
+        }
+
+        self.depth -= 1;
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => Expr::Or(exprs),
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, RenderError> {
+        let mut exprs = vec![self.not()?];
+        while self.keyword("and") {
+            exprs.push(self.not()?);
+        }
+
+        Ok(match exprs.len() {
+            1 => exprs.remove(0),
+            _ => Expr::And(exprs),
+        })
+    }
+
+    fn not(&mut self) -> Result<Expr, RenderError> {
+        if self.keyword("not") {
+            self.enter()?;
+            let expr = self.not()?;
+            self.depth -= 1;
+            Ok(Expr::Not(Box::new(expr)))
+        } else {
+            self.compare()
+        }
+    }
+
+    fn compare(&mut self) -> Result<Expr, RenderError> {
+        let left = self.primary()?;
+
+        match self.peek() {
+            Some(&Token::Op(op)) => {
+                self.pos += 1;
+                Ok(Expr::Compare(Box::new(left), op, Box::new(self.primary()?)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, RenderError> {
+        let token = self
+            .next()
+            .cloned()
+            .ok_or_else(|| RenderError::new("incomplete expression"))?;
+
+        match token {
+            Token::Str(s) => Ok(Expr::Literal(Value::String(s))),
+            Token::Num(n) => Ok(Expr::Literal(Value::Number(n))),
+            Token::LParen => {
+                let expr = self.or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(RenderError::new("missing `)`")),
+                }
+            }
+            Token::Ident(ident) => match ident.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                _ if self.peek() == Some(&Token::LParen) => {
+                    self.pos += 1;
+                    self.call(ident)
+                }
+                _ => parse_path(&ident).map(Expr::Path),
+            },
+            token => Err(RenderError::new(format!(
+                "unexpected {token:?} in expression"
+            ))),
+        }
+    }
+
+    fn call(&mut self, name: String) -> Result<Expr, RenderError> {
+        let mut args = Vec::new();
+
+        if self.peek() == Some(&Token::RParen) {
+            self.pos += 1;
+            return Ok(Expr::Call(name, args));
+        }
+
+        loop {
+            args.push(self.or()?);
+            match self.next() {
+                Some(Token::Comma) => continue,
+                Some(Token::RParen) => return Ok(Expr::Call(name, args)),
+                _ => return Err(RenderError::new("missing `)`")),
+            }
+        }
+    }
+}
+
+/// Parses a variable path like `items[0].name`.
+fn parse_path(path: &str) -> Result<Vec<Segment>, RenderError> {
+    let invalid = || RenderError::new(format!("invalid variable `{path}`"));
+    let mut segments = Vec::new();
+
+    for part in path.split('.') {
+        let (key, mut indices) = match part.find('[') {
+            Some(idx) => part.split_at(idx),
+            None => (part, ""),
+        };
+
+        if key.is_empty() || key.contains(']') {
+            return Err(invalid());
+        }
+        segments.push(Segment::Key(key.to_owned()));
+
+        while !indices.is_empty() {
+            let end = indices.find(']').ok_or_else(invalid)?;
+            let index = indices[1..end].parse().map_err(|_| invalid())?;
+            segments.push(Segment::Index(index));
+            indices = &indices[end + 1..];
+            if !indices.is_empty() && !indices.starts_with('[') {
+                return Err(invalid());
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+struct Renderer<'a> {
+    data: &'a Value,
+    /// current element and index of enclosing `each` loops
+    loops: Vec<(Value, usize)>,
+    escape_html: bool,
+    depth: usize,
+}
+
+impl Renderer<'_> {
+    fn render(
+        &mut self,
+        nodes: &[Node],
+        out: &mut String,
+    ) -> Result<(), RenderError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => out.push_str(text),
+                Node::Output {
+                    expr: Expr::Call(name, args),
+                    ..
+                } if name == "render_dynamic_content" => {
+                    self.render_dynamic_content(args, out)?
+                }
+                Node::Output { expr, escape } => {
+                    let value = self.eval(expr)?;
+                    let text = match value {
+                        Value::Null => String::new(),
+                        Value::String(s) => s,
+                        value => value.to_string(),
+                    };
+
+                    if *escape && self.escape_html {
+                        escape_html(&text, out);
+                    } else {
+                        out.push_str(&text);
+                    }
+                }
+                Node::If {
+                    branches,
+                    otherwise,
+                } => {
+                    let mut body = otherwise;
+                    for (cond, branch) in branches {
+                        if truthy(&self.eval(cond)?) {
+                            body = branch;
+                            break;
+                        }
+                    }
+                    self.render(body, out)?;
+                }
+                Node::Each { list, body } => {
+                    if let Value::Array(list) = self.eval(list)? {
+                        for (idx, item) in list.into_iter().enumerate() {
+                            self.loops.push((item, idx));
+                            let res = self.render(body, out);
+                            self.loops.pop();
+                            res?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renders a template taken from substitution data, usually from
+    /// `dynamic_html` or `dynamic_plain`.
+    fn render_dynamic_content(
+        &mut self,
+        args: &[Expr],
+        out: &mut String,
+    ) -> Result<(), RenderError> {
+        let template = match args {
+            [arg] => self.eval(arg)?,
+            _ => {
+                return Err(RenderError::new(
+                    "render_dynamic_content takes one argument",
+                ))
+            }
+        };
+
+        let template = match template {
+            Value::Null => return Ok(()),
+            Value::String(template) => template,
+            _ => {
+                return Err(RenderError::new(
+                    "dynamic content must be a string",
+                ))
+            }
+        };
+
+        if self.depth >= MAX_DEPTH {
+            return Err(RenderError::new("dynamic content nested too deeply"));
+        }
+
+        let nodes = parse(&template)?;
+        self.depth += 1;
+        let res = self.render(&nodes, out);
+        self.depth -= 1;
+        res
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Value, RenderError> {
+        Ok(match expr {
+            Expr::Literal(value) => value.clone(),
+            Expr::Path(path) => self.lookup(path),
+            Expr::Not(expr) => Value::Bool(!truthy(&self.eval(expr)?)),
+            Expr::And(exprs) => {
+                let mut value = Value::Null;
+                for expr in exprs {
+                    value = self.eval(expr)?;
+                    if !truthy(&value) {
+                        break;
+                    }
+                }
+                value
+            }
+            Expr::Or(exprs) => {
+                let mut value = Value::Null;
+                for expr in exprs {
+                    value = self.eval(expr)?;
+                    if truthy(&value) {
+                        break;
+                    }
+                }
+                value
+            }
+            Expr::Compare(left, op, right) => {
+                Value::Bool(compare(&self.eval(left)?, op, &self.eval(right)?))
+            }
+            Expr::Call(name, args) => match (name.as_str(), &args[..]) {
+                ("empty", [arg]) => Value::Bool(match self.eval(arg)? {
+                    Value::Null => true,
+                    Value::String(s) => s.is_empty(),
+                    Value::Array(list) => list.is_empty(),
+                    Value::Object(map) => map.is_empty(),
+                    _ => false,
+                }),
+                ("render_dynamic_content", _) => {
+                    return Err(RenderError::new(
+                        "render_dynamic_content can only be used for output",
+                    ))
+                }
+                _ => {
+                    return Err(RenderError::new(format!(
+                        "unknown function `{name}` or wrong number of arguments"
+                    )))
+                }
+            },
+        })
+    }
+
+    /// Resolves a variable, missing values are `null`.
+    fn lookup(&self, path: &[Segment]) -> Value {
+        let (first, rest) = match path.split_first() {
+            Some((Segment::Key(first), rest)) => (first.as_str(), rest),
+            _ => return Value::Null,
+        };
+
+        let mut value = match (first, self.loops.last()) {
+            ("loop_var", Some((item, _))) => item.clone(),
+            ("loop_index", Some((_, idx))) => Value::from(*idx),
+            _ => self.data.get(first).cloned().unwrap_or_default(),
+        };
+
+        for segment in rest {
+            value = match segment {
+                Segment::Key(key) => value.get(key),
+                Segment::Index(idx) => value.get(idx),
+            }
+            .cloned()
+            .unwrap_or_default();
+        }
+
+        value
+    }
+}
+
+/// `null`, `false`, `0`, empty strings, arrays and objects are false
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(list) => !list.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+fn compare(left: &Value, op: &str, right: &Value) -> bool {
+    let ord = match (left, right) {
+        (Value::Number(l), Value::Number(r)) => {
+            l.as_f64().partial_cmp(&r.as_f64())
+        }
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+
+    let eq = ord.map_or(left == right, Ordering::is_eq);
+
+    match op {
+        "==" => eq,
+        "!=" => !eq,
+        "<" => ord == Some(Ordering::Less),
+        "<=" => matches!(ord, Some(Ordering::Less | Ordering::Equal)),
+        ">" => ord == Some(Ordering::Greater),
+        ">=" => matches!(ord, Some(Ordering::Greater | Ordering::Equal)),
+        _ => false,
+    }
+}
+
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transmission::{EmailAddress, Options};
+    use serde_json::json;
+
+    fn render(template: &str, data: Value) -> String {
+        render_template(template, &data, true).unwrap()
+    }
+
+    #[test]
+    fn variables_and_defaults() {
+        let data = json!({
+            "name": "Wilma",
+            "empty": "",
+            "user": { "tags": ["a", "b"], "age": 42 }
+        });
+
+        assert_eq!(
+            render(
+                "Hi {{ name }}, {{user.tags[1]}} {{ user.age }} {{missing}}!",
+                data.clone()
+            ),
+            "Hi Wilma, b 42 !"
+        );
+        assert_eq!(
+            render(
+                "{{first_name or 'you great person'}} {{empty or \"none\"}} {{ name or 'x' }}",
+                data
+            ),
+            "you great person none Wilma"
+        );
+    }
+
+    #[test]
+    fn escaping() {
+        let data = json!({ "html": "<b>\"Tom & Jerry's\"</b>" });
+
+        assert_eq!(
+            render("{{html}}", data.clone()),
+            "&lt;b&gt;&quot;Tom &amp; Jerry&#39;s&quot;&lt;/b&gt;"
+        );
+        assert_eq!(
+            render("{{{ html }}}", data.clone()),
+            "<b>\"Tom & Jerry's\"</b>"
+        );
+        assert_eq!(
+            render_template("{{html}}", &data, false).unwrap(),
+            "<b>\"Tom & Jerry's\"</b>"
+        );
+    }
+
+    #[test]
+    fn conditionals() {
+        let template = "{{if level == 'gold'}}G{{elseif level == 'silver' and points >= 100}}S{{elseif not empty(level)}}B{{else}}-{{end}}";
+
+        assert_eq!(render(template, json!({ "level": "gold" })), "G");
+        assert_eq!(
+            render(template, json!({ "level": "silver", "points": 100 })),
+            "S"
+        );
+        assert_eq!(
+            render(template, json!({ "level": "silver", "points": 99.5 })),
+            "B"
+        );
+        assert_eq!(render(template, json!({})), "-");
+
+        assert_eq!(
+            render(
+                "{{ if (a or b) and not c }}yes{{ end }}",
+                json!({ "b": 1, "c": false })
+            ),
+            "yes"
+        );
+        assert_eq!(render("{{if list}}x{{end}}", json!({ "list": [] })), "");
+    }
+
+    #[test]
+    fn number_literals() {
+        assert_eq!(render("{{ 1 }} {{ -2 }} {{ 1.5 }}", json!({})), "1 -2 1.5");
+        assert_eq!(render("{{ count or 0 }}", json!({})), "0");
+        assert_eq!(render("{{ count or -1 }}", json!({ "count": 0 })), "-1");
+        assert_eq!(
+            render(
+                "{{ if count == 2 and temp < -0.5 }}x{{ end }}",
+                json!({ "count": 2.0, "temp": -1 })
+            ),
+            "x"
+        );
+    }
+
+    #[test]
+    fn loops() {
+        let data = json!({
+            "orders": [
+                { "id": 1, "items": ["a", "b"] },
+                { "id": 2, "items": [] }
+            ]
+        });
+
+        assert_eq!(
+            render(
+                "{{each orders}}#{{loop_var.id}}@{{loop_index}}:{{each loop_var.items}}{{loop_var}}{{loop_index}}{{end}};{{end}}",
+                data
+            ),
+            "#1@0:a0b1;#2@1:;"
+        );
+        assert_eq!(render("{{each missing}}x{{end}}", json!({})), "");
+    }
+
+    #[test]
+    fn dynamic_content() {
+        let data = json!({
+            "name": "<Wilma>",
+            "dynamic_html": {
+                "greeting": "<p>Hello {{name}}</p>",
+                "nested": "[{{render_dynamic_content(dynamic_html.greeting)}}]",
+                "loop": "{{render_dynamic_content(dynamic_html.loop)}}"
+            }
+        });
+
+        assert_eq!(
+            render(
+                "{{ render_dynamic_content(dynamic_html.nested) }}{{render_dynamic_content(dynamic_html.missing)}}",
+                data.clone()
+            ),
+            "[<p>Hello &lt;Wilma&gt;</p>]"
+        );
+
+        let err = render_template(
+            "{{render_dynamic_content(dynamic_html.loop)}}",
+            &data,
+            true,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nested too deeply"));
+    }
+
+    #[test]
+    fn syntax_errors() {
+        for template in [
+            "{{ name",
+            "{{{ name }}",
+            "{{ }}",
+            "{{if a}}x",
+            "{{if a}}x{{else}}y{{elseif b}}z{{end}}",
+            "{{each a}}x{{else}}",
+            "{{end}}",
+            "{{ else x }}",
+            "{{ 'unterminated }}",
+            "{{ a == }}",
+            "{{ a b }}",
+            "{{ unknown(a) }}",
+            "{{ a[x] }}",
+            "{{ if render_dynamic_content(a) }}{{ end }}",
+        ] {
+            assert!(
+                render_template(template, &json!({ "a": true }), true).is_err(),
+                "`{template}` should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn braces_in_strings() {
+        let data = json!({});
+        assert_eq!(
+            render_template("{{ a or 'x}}y' }}!", &data, true).unwrap(),
+            "x}}y!"
+        );
+        assert_eq!(
+            render_template(r#"{{{ a or "}}}\"" }}}"#, &data, false).unwrap(),
+            "}}}\""
+        );
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| {
+            format!("{}x{}", "{{if a}}".repeat(depth), "{{end}}".repeat(depth))
+        };
+        let data = json!({ "a": true });
+
+        assert_eq!(
+            render_template(&nested(MAX_NESTING), &data, true).unwrap(),
+            "x"
+        );
+
+        let err = render_template(&nested(200_000), &data, true).unwrap_err();
+        assert!(err.to_string().contains("nested deeper"));
+
+        for template in [
+            format!("{{{{ {}a }}}}", "not ".repeat(200_000)),
+            format!(
+                "{{{{ {}a{} }}}}",
+                "(".repeat(200_000),
+                ")".repeat(200_000)
+            ),
+        ] {
+            let err = render_template(&template, &data, true).unwrap_err();
+            assert!(err.to_string().contains("nested deeper"));
+        }
+
+        // long chains do not nest
+        let chain = format!("{{{{ b{} }}}}", " or b".repeat(200_000));
+        assert_eq!(render_template(&chain, &data, true).unwrap(), "");
+    }
+
+    #[test]
+    fn render_message() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .subject("Hi {{name}} <{{address.email}}>")
+            .text("{{plan}} {{source}} {{address.name or 'friend'}}")
+            .html("<p>{{name}}</p>")
+            .metadata(json!({ "source": "metadata", "plan": "metadata" }))
+            .substitution_data(
+                json!({ "name": "<Everyone>", "plan": "basic" }),
+            );
+
+        let recipient = Recipient::with_substitution(
+            EmailAddress::new("wilma@example.com", "Wilma"),
+            json!({ "name": "<Wilma>" }),
+        );
+
+        assert_eq!(
+            email.render(&recipient).unwrap(),
+            RenderedContent {
+                subject: "Hi <Wilma> <wilma@example.com>".to_owned(),
+                text: Some("basic metadata Wilma".to_owned()),
+                html: Some("<p>&lt;Wilma&gt;</p>".to_owned()),
+                amp_html: None,
+            }
+        );
+
+        email.options(Options::new().perform_substitutions(false));
+        assert_eq!(
+            email.render(&recipient).unwrap().html.unwrap(),
+            "<p>{{name}}</p>"
+        );
+
+        email.template_id("offer");
+        assert!(email.render(&recipient).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{Content, Options, RecipientSet};
+
+/// Transmission result returned by the API
+///
+#[derive(Debug, Deserialize)]
+pub struct ApiResponse {
+    pub total_rejected_recipients: usize,
+    pub total_accepted_recipients: usize,
+    pub id: String,
+}
+
+/// Processing state of a transmission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransmissionState {
+    /// Scheduled or waiting to be processed
+    Submitted,
+    /// Messages are being generated
+    Generating,
+    /// All messages were generated
+    Success,
+    /// Canceled before generation finished
+    Canceled,
+    /// A state not known to this version of the crate
+    #[serde(other)]
+    Unknown,
+}
+
+/// Full transmission returned when retrieving a transmission by id
+///
+/// `options`, `content` and `recipients` are returned as sent in the original
+/// request.
+///
+/// ```rust
+/// use sparklepost::transmission::{Content, RecipientSet, TransmissionDetails};
+///
+/// let details: TransmissionDetails = serde_json::from_str(
+///     r#"{
+///         "id": "11714265276873",
+///         "state": "submitted",
+///         "content": {
+///             "from": "Example <marketing@example.com>",
+///             "subject": "Hi",
+///             "html": "<p>Hi!</p>"
+///         },
+///         "recipients": [{
+///             "address": { "email": "wilma@example.com" },
+///             "tags": ["welcome"]
+///         }]
+///     }"#,
+/// )
+/// .unwrap();
+///
+/// let content = match details.content {
+///     Some(Content::Inline(ref content)) => content,
+///     _ => unreachable!(),
+/// };
+/// assert_eq!(content.from().email(), "marketing@example.com");
+/// assert_eq!(content.subject(), "Hi");
+/// assert_eq!(content.html(), Some("<p>Hi!</p>"));
+///
+/// let recipients = match details.recipients {
+///     Some(RecipientSet::LocalList(ref list)) => list,
+///     _ => unreachable!(),
+/// };
+/// assert_eq!(recipients[0].address().email(), "wilma@example.com");
+/// assert_eq!(recipients[0].get_tags(), ["welcome"]);
+/// ```
+///
+/// For more info see <https://developers.sparkpost.com/api/transmissions/#transmissions-get-retrieve-a-transmission>.
+#[derive(Debug, Deserialize)]
+pub struct TransmissionDetails {
+    pub id: String,
+    pub state: TransmissionState,
+    pub description: Option<String>,
+    pub campaign_id: Option<String>,
+    pub return_path: Option<String>,
+    pub metadata: Option<Value>,
+    pub substitution_data: Option<Value>,
+    pub options: Option<Options>,
+    pub content: Option<Content>,
+    pub recipients: Option<RecipientSet>,
+
+    /// number of recipients targeted
+    pub num_rcpts: Option<u64>,
+    /// number of messages generated so far
+    pub num_generated: Option<u64>,
+    /// number of messages that could not be generated
+    pub num_failed_generation: Option<u64>,
+    /// number of recipients rejected as invalid
+    pub num_invalid_recipients: Option<u64>,
+    /// number of recipients per chunk of a stored recipient list
+    pub rcpt_list_chunk_size: Option<u64>,
+    /// number of chunks a stored recipient list was split into
+    pub rcpt_list_total_chunks: Option<u64>,
+
+    pub generation_start_time: Option<DateTime<Utc>>,
+    pub generation_end_time: Option<DateTime<Utc>>,
+}
+
+/// Transmission as returned when listing transmissions
+#[derive(Debug, Deserialize)]
+pub struct TransmissionSummary {
+    pub id: String,
+    pub state: Option<TransmissionState>,
+    pub description: Option<String>,
+    pub campaign_id: Option<String>,
+    pub content: Option<Content>,
+}
+
+/// Response body of the retrieve endpoint, i.e. `{ "transmission": ... }`
+#[cfg(any(test, feature = "blocking", feature = "async"))]
+#[derive(Debug, Deserialize)]
+pub(super) struct TransmissionWrapper {
+    pub(super) transmission: TransmissionDetails,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone as _;
+
+    #[test]
+    fn transmission_details() {
+        let json = r#"{
+            "transmission": {
+                "id": "11714265276872",
+                "description": "Christmas Campaign Email",
+                "state": "success",
+                "campaign_id": "christmas_campaign",
+                "return_path": "bounces@example.com",
+                "options": { "open_tracking": true, "click_tracking": true },
+                "content": { "template_id": "christmas_offer" },
+                "recipients": { "list_id": "christmas_list" },
+                "metadata": { "user_type": "students" },
+                "substitution_data": { "sender": "Big Store Team" },
+                "num_rcpts": 10,
+                "num_generated": 9,
+                "num_failed_generation": 1,
+                "num_invalid_recipients": 0,
+                "rcpt_list_chunk_size": 100000,
+                "rcpt_list_total_chunks": 1,
+                "generation_start_time": "2019-01-01T00:00:00+00:00",
+                "generation_end_time": "2019-01-01T00:01:00+00:00"
+            }
+        }"#;
+
+        let details = serde_json::from_str::<TransmissionWrapper>(json)
+            .unwrap()
+            .transmission;
+
+        assert_eq!(details.id, "11714265276872");
+        assert_eq!(details.state, TransmissionState::Success);
+        assert_eq!(details.campaign_id.as_deref(), Some("christmas_campaign"));
+        assert_eq!(details.num_generated, Some(9));
+        assert_eq!(details.num_failed_generation, Some(1));
+        assert_eq!(details.rcpt_list_total_chunks, Some(1));
+        assert_eq!(
+            details.options,
+            Some(Options::new().open_tracking(true).click_tracking(true))
+        );
+        assert!(matches!(
+            details.content,
+            Some(Content::Template {
+                ref template_id,
+                use_draft_template: false,
+            }) if template_id == "christmas_offer"
+        ));
+        assert!(matches!(
+            details.recipients,
+            Some(RecipientSet::ListName(ref list)) if list == "christmas_list"
+        ));
+        assert_eq!(
+            details.generation_end_time,
+            Some(Utc.with_ymd_and_hms(2019, 1, 1, 0, 1, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn inline_transmission_details() {
+        let json = r#"{
+            "id": "11714265276873",
+            "state": "submitted",
+            "options": {},
+            "content": {
+                "from": "Example <marketing@example.com>",
+                "subject": "Hi",
+                "text": "Hi!"
+            },
+            "recipients": [{ "address": { "email": "to@example.com" } }]
+        }"#;
+
+        let details: TransmissionDetails = serde_json::from_str(json).unwrap();
+
+        assert_eq!(details.options, Some(Options::default()));
+        assert!(matches!(
+            details.content,
+            Some(Content::Inline(ref content))
+                if content.from().email() == "marketing@example.com"
+        ));
+        assert!(matches!(
+            details.recipients,
+            Some(RecipientSet::LocalList(ref list)) if list.len() == 1
+        ));
+    }
+
+    #[test]
+    fn transmission_summaries() {
+        let json = r#"[
+            {
+                "id": "11714265276872",
+                "state": "submitted",
+                "campaign_id": "thanksgiving",
+                "description": "",
+                "content": { "template_id": "thanksgiving_offer" }
+            },
+            {
+                "id": "11714265276873",
+                "state": "rescheduled"
+            }
+        ]"#;
+
+        let list: Vec<TransmissionSummary> =
+            serde_json::from_str(json).unwrap();
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].state, Some(TransmissionState::Submitted));
+        assert_eq!(list[0].campaign_id.as_deref(), Some("thanksgiving"));
+        assert!(matches!(
+            list[0].content,
+            Some(Content::Template { ref template_id, .. })
+                if template_id == "thanksgiving_offer"
+        ));
+        assert_eq!(list[1].state, Some(TransmissionState::Unknown));
+        assert_eq!(list[1].campaign_id, None);
+        assert!(list[1].content.is_none());
+    }
+}
@@ -0,0 +1,32 @@
+//! Fixtures shared by the transmission tests
+
+use super::Message;
+
+/// Successful response to sending [`message`]
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) const SEND_RESULTS: &str = r#"{
+    "results": {
+        "total_rejected_recipients": 0,
+        "total_accepted_recipients": 1,
+        "id": "11668787484950529"
+    }
+}"#;
+
+/// Valid message with a single recipient
+pub(crate) fn message() -> Message {
+    let mut email = Message::new("sender@example.com");
+    email
+        .add_recipient("recipient@example.com")
+        .subject("Test")
+        .text("Test");
+    email
+}
+
+/// API key for tests against the real API, read from `SPARKPOST_API_KEY`
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn get_api_key() -> String {
+    use dotenvy::dotenv;
+    use std::env;
+    dotenv().ok();
+    env::var("SPARKPOST_API_KEY").expect("SPARKPOST_API_KEY must be set")
+}
@@ -0,0 +1,570 @@
+use std::fmt;
+
+use super::{
+    message::{Content, InlineContent, Message, MAX_MESSAGE_SIZE},
+    models::{RecipientKind, RecipientSet},
+};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::Error;
+
+/// Problem found by [`Message::validate`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The message has no recipients
+    NoRecipients,
+
+    /// A recipient address is missing an `@`
+    InvalidRecipient(String),
+
+    /// CC or BCC recipients are given without any "To" recipient, whose
+    /// addresses they would see in their `To` header
+    NoToRecipient,
+
+    /// The sender address is missing an `@`
+    InvalidSender(String),
+
+    /// Inline content has an empty subject
+    EmptySubject,
+
+    /// Inline content has neither a text, HTML nor AMP HTML body
+    EmptyBody,
+
+    /// Stored template content has an empty template id
+    EmptyTemplateId,
+
+    /// RFC 822 content is empty
+    EmptyRfc822,
+
+    /// An inline content field, i.e. `subject` or `headers`, was set on
+    /// stored template or RFC 822 content and would not be sent, see
+    /// [`Content`]
+    InlineFieldNotApplicable(String),
+
+    /// CC recipients are given with stored template or RFC 822 content, which
+    /// has no `CC` header listing them, so they would receive a blind copy
+    CcNotApplicable,
+
+    /// AMP HTML body is given without an HTML fallback, which mail clients
+    /// without AMP support display instead
+    MissingHtmlFallback,
+
+    /// An inline image is not referenced as `cid:<name>` in the HTML body
+    UnreferencedInlineImage(String),
+
+    /// An attachment or inline image name is empty, longer than 255 bytes or
+    /// contains control characters, quotes, slashes or backslashes
+    InvalidAttachmentName(String),
+
+    /// A header name is empty or contains characters other than printable
+    /// ASCII without `:`
+    InvalidHeaderName(String),
+
+    /// The value of the named header contains a line break, which would
+    /// start another header
+    InvalidHeaderValue(String),
+
+    /// The JSON payload exceeds [`MAX_MESSAGE_SIZE`]
+    TooLarge { size: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::NoRecipients => f.write_str("no recipients"),
+            ValidationError::InvalidRecipient(email) => {
+                write!(f, "invalid recipient address `{email}`")
+            }
+            ValidationError::NoToRecipient => {
+                f.write_str("CC or BCC recipients without a \"To\" recipient")
+            }
+            ValidationError::InvalidSender(email) => {
+                write!(f, "invalid sender address `{email}`")
+            }
+            ValidationError::EmptySubject => f.write_str("empty subject"),
+            ValidationError::EmptyBody => {
+                f.write_str("no text, HTML or AMP HTML body")
+            }
+            ValidationError::EmptyTemplateId => {
+                f.write_str("empty template id")
+            }
+            ValidationError::EmptyRfc822 => {
+                f.write_str("empty RFC 822 message")
+            }
+            ValidationError::InlineFieldNotApplicable(field) => write!(
+                f,
+                "`{field}` cannot be set on stored template or RFC 822 content"
+            ),
+            ValidationError::CcNotApplicable => f.write_str(
+                "CC recipients require inline content to be listed in the `CC` header",
+            ),
+            ValidationError::MissingHtmlFallback => {
+                f.write_str("AMP HTML body requires an HTML fallback")
+            }
+            ValidationError::UnreferencedInlineImage(name) => write!(
+                f,
+                "inline image `{name}` is not referenced in the HTML body"
+            ),
+            ValidationError::InvalidAttachmentName(name) => {
+                write!(f, "invalid attachment name `{name}`")
+            }
+            ValidationError::InvalidHeaderName(name) => {
+                write!(f, "invalid header name `{name}`")
+            }
+            ValidationError::InvalidHeaderValue(name) => {
+                write!(f, "line break in value of header `{name}`")
+            }
+            ValidationError::TooLarge { size } => write!(
+                f,
+                "message size of {size} bytes exceeds the maximum of {MAX_MESSAGE_SIZE} bytes"
+            ),
+        }
+    }
+}
+
+impl Message {
+    /// Checks the message for problems the API would reject, or which
+    /// produce broken emails, without sending it.
+    ///
+    /// Clients only run this before sending when enabled with
+    /// [`TransmissionBuilder::validate`](super::TransmissionBuilder::validate).
+    /// Inline fields and CC recipients set on other content, AMP HTML without
+    /// HTML fallback and line breaks in header values are always checked.
+    ///
+    /// ```rust
+    /// use sparklepost::transmission::{Message, ValidationError};
+    ///
+    /// let mut email = Message::new("marketing@example.sink.sparkpostmail.com");
+    /// email.text("Hello!");
+    ///
+    /// assert_eq!(
+    ///     email.validate().unwrap_err(),
+    ///     vec![ValidationError::NoRecipients, ValidationError::EmptySubject],
+    /// );
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = self.check(true);
+
+        // serialization errors are reported when sending
+        if let Ok(body) = serde_json::to_vec(self) {
+            check_size(&body, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Serializes the message as request body, failing if content would be
+    /// dropped or the email would be broken, and on any problem found by
+    /// [`validate`](Message::validate) if `validate` is set.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn to_request_body(
+        &self,
+        validate: bool,
+    ) -> Result<Vec<u8>, Error> {
+        let mut errors = self.check(validate);
+        let body = serde_json::to_vec(self)?;
+
+        if validate {
+            check_size(&body, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(body)
+        } else {
+            Err(Error::Validation(errors))
+        }
+    }
+
+    /// Collects all problems but the payload size, or only those that drop
+    /// content or break the email unless `all` is set.
+    fn check(&self, all: bool) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        match self.recipients {
+            RecipientSet::LocalList(ref list) if all => {
+                if list.is_empty() {
+                    errors.push(ValidationError::NoRecipients);
+                }
+
+                for rec in list {
+                    if !is_email(&rec.address.email) {
+                        errors.push(ValidationError::InvalidRecipient(
+                            rec.address.email.clone(),
+                        ));
+                    }
+                }
+
+                if !list.is_empty()
+                    && list.iter().all(|rec| rec.kind != RecipientKind::To)
+                {
+                    errors.push(ValidationError::NoToRecipient);
+                }
+            }
+            RecipientSet::ListName(ref name) if all => {
+                if name.is_empty() {
+                    errors.push(ValidationError::NoRecipients);
+                }
+            }
+            RecipientSet::LocalList(_) | RecipientSet::ListName(_) => {}
+        }
+
+        for field in self.ignored_fields() {
+            errors.push(ValidationError::InlineFieldNotApplicable(
+                (*field).to_owned(),
+            ));
+        }
+
+        // only inline content gets a `CC` header
+        if let RecipientSet::LocalList(ref list) = self.recipients {
+            if !matches!(self.content, Content::Inline(_))
+                && list.iter().any(|rec| rec.kind == RecipientKind::Cc)
+            {
+                errors.push(ValidationError::CcNotApplicable);
+            }
+        }
+
+        match self.content {
+            Content::Inline(ref content) => {
+                check_inline(content, all, &mut errors)
+            }
+            Content::Template {
+                ref template_id, ..
+            } => {
+                if all && template_id.is_empty() {
+                    errors.push(ValidationError::EmptyTemplateId);
+                }
+            }
+            Content::Rfc822 { ref email_rfc822 } => {
+                if all && email_rfc822.trim().is_empty() {
+                    errors.push(ValidationError::EmptyRfc822);
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+fn check_size(body: &[u8], errors: &mut Vec<ValidationError>) {
+    if body.len() > MAX_MESSAGE_SIZE {
+        errors.push(ValidationError::TooLarge { size: body.len() });
+    }
+}
+
+fn check_inline(
+    content: &InlineContent,
+    all: bool,
+    errors: &mut Vec<ValidationError>,
+) {
+    if all {
+        if !is_email(&content.from.email) {
+            errors.push(ValidationError::InvalidSender(
+                content.from.email.clone(),
+            ));
+        }
+
+        if content.subject.trim().is_empty() {
+            errors.push(ValidationError::EmptySubject);
+        }
+
+        if content.text.is_none()
+            && content.html.is_none()
+            && content.amp_html.is_none()
+        {
+            errors.push(ValidationError::EmptyBody);
+        }
+    }
+
+    if content.amp_html.is_some() && content.html.is_none() {
+        errors.push(ValidationError::MissingHtmlFallback);
+    }
+
+    for (name, value) in &content.headers {
+        if value.contains(['\r', '\n']) {
+            errors.push(ValidationError::InvalidHeaderValue(name.clone()));
+        }
+    }
+
+    let html = content.html.as_deref().unwrap_or_default();
+
+    for image in &content.inline_images {
+        if all && !is_attachment_name(&image.name) {
+            errors.push(ValidationError::InvalidAttachmentName(
+                image.name.clone(),
+            ));
+        }
+
+        if all && !html.contains(&format!("cid:{}", image.name)) {
+            errors.push(ValidationError::UnreferencedInlineImage(
+                image.name.clone(),
+            ));
+        }
+    }
+
+    if !all {
+        return;
+    }
+
+    for attachment in &content.attachments {
+        if !is_attachment_name(&attachment.name) {
+            errors.push(ValidationError::InvalidAttachmentName(
+                attachment.name.clone(),
+            ));
+        }
+    }
+
+    for name in content.headers.keys() {
+        if !is_header_name(name) {
+            errors.push(ValidationError::InvalidHeaderName(name.clone()));
+        }
+    }
+}
+
+/// Loose check for `local@domain`, the API performs the full validation.
+fn is_email(email: &str) -> bool {
+    match email.rsplit_once('@') {
+        Some((local, domain)) => !local.is_empty() && !domain.is_empty(),
+        None => false,
+    }
+}
+
+fn is_attachment_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 255
+        && !name
+            .chars()
+            .any(|c| c.is_control() || matches!(c, '"' | '/' | '\\'))
+}
+
+/// Header field names consist of printable ASCII except `:`, see RFC 5322.
+fn is_header_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| matches!(b, 33..=126) && b != b':')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transmission::{testing::message, Attachment, InlineImage};
+
+    #[test]
+    fn valid_messages() {
+        message().validate().unwrap();
+
+        let mut email = Message::default();
+        email.recipient_list("my_list").template_id("offer");
+        email.validate().unwrap();
+
+        let mut email = Message::from_rfc822("Subject: Hi\r\n\r\nHi!\r\n");
+        email.add_recipient("to@example.com");
+        email.validate().unwrap();
+    }
+
+    #[test]
+    fn required_fields() {
+        let mut email = Message::new("sender");
+        email.add_recipient("to.example.com");
+
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![
+                ValidationError::InvalidRecipient("to.example.com".to_owned()),
+                ValidationError::InvalidSender("sender".to_owned()),
+                ValidationError::EmptySubject,
+                ValidationError::EmptyBody,
+            ]
+        );
+
+        let mut email = Message::default();
+        email.template_id("");
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![
+                ValidationError::NoRecipients,
+                ValidationError::EmptyTemplateId
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_fields_on_other_content() {
+        let mut email = message();
+        email
+            .template_id("offer")
+            .header("X-Campaign", "sale")
+            .list_unsubscribe("https://example.com/unsubscribe", None)
+            .subject("Hi");
+
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![
+                ValidationError::InlineFieldNotApplicable("headers".to_owned()),
+                ValidationError::InlineFieldNotApplicable("subject".to_owned()),
+            ]
+        );
+
+        let mut email = Message::from_rfc822("Subject: Hi\r\n\r\nHi!\r\n");
+        email
+            .add_recipient("to@example.com")
+            .reply_to("a@example.com");
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![ValidationError::InlineFieldNotApplicable(
+                "reply_to".to_owned()
+            )]
+        );
+
+        // replacing the content starts over
+        email.template_id("offer");
+        email.validate().unwrap();
+    }
+
+    #[test]
+    fn cc_on_other_content() {
+        let mut email = message();
+        email.add_cc("cc@example.com").template_id("offer");
+
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![ValidationError::CcNotApplicable]
+        );
+
+        let mut email = Message::from_rfc822("Subject: Hi\r\n\r\nHi!\r\n");
+        email
+            .add_recipient("to@example.com")
+            .add_bcc("bcc@example.com");
+        email.validate().unwrap();
+
+        email.add_cc("cc@example.com");
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![ValidationError::CcNotApplicable]
+        );
+    }
+
+    #[test]
+    fn copies_without_to_recipient() {
+        let mut email = Message::new("sender@example.com");
+        email
+            .subject("Hi")
+            .text("Hi!")
+            .add_cc("cc@example.com")
+            .add_bcc("bcc@example.com");
+
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![ValidationError::NoToRecipient]
+        );
+
+        email.add_recipient("to@example.com");
+        email.validate().unwrap();
+    }
+
+    #[test]
+    fn body_checks() {
+        let mut email = message();
+        email.amp_html("<html ⚡4email></html>").add_inline_image(
+            InlineImage::from_data("logo.png", "image/png", "aGk="),
+        );
+
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![
+                ValidationError::MissingHtmlFallback,
+                ValidationError::UnreferencedInlineImage("logo.png".to_owned()),
+            ]
+        );
+
+        email.html(r#"<img src="cid:logo.png">"#);
+        email.validate().unwrap();
+    }
+
+    #[test]
+    fn names() {
+        let mut email = message();
+        email
+            .add_attachment(Attachment::from_data("report.pdf", "a", "aGk="))
+            .add_attachment(Attachment::from_data("../report.pdf", "a", "aGk="))
+            .add_attachment(Attachment::from_data("", "a", "aGk="))
+            .header("X-Campaign", "sale")
+            .header("X Campaign", "sale")
+            .header("X-Campaign:", "sale");
+
+        assert_eq!(
+            email.validate().unwrap_err(),
+            vec![
+                ValidationError::InvalidAttachmentName(
+                    "../report.pdf".to_owned()
+                ),
+                ValidationError::InvalidAttachmentName(String::new()),
+                ValidationError::InvalidHeaderName("X Campaign".to_owned()),
+                ValidationError::InvalidHeaderName("X-Campaign:".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn header_injection() {
+        let mut email = message();
+        email
+            .header("X-Campaign", "sale\r\nBcc: eve@example.com")
+            .list_unsubscribe("https://example.com/unsubscribe\n", None);
+
+        let errors = vec![
+            ValidationError::InvalidHeaderValue("List-Unsubscribe".to_owned()),
+            ValidationError::InvalidHeaderValue("X-Campaign".to_owned()),
+        ];
+        assert_eq!(email.validate().unwrap_err(), errors);
+        assert_eq!(email.check(false), errors);
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    #[test]
+    fn request_body() {
+        let mut email = message();
+        email.subject("");
+
+        let body = email.to_request_body(false).unwrap();
+        assert_eq!(body, serde_json::to_vec(&email).unwrap());
+
+        let err = email.to_request_body(true).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Validation(ref errors)
+                if errors[..] == [ValidationError::EmptySubject]
+        ));
+
+        // references may only be added by substitutions
+        email.subject("Hi").add_inline_image(InlineImage::from_data(
+            "logo.png",
+            "image/png",
+            "aGk=",
+        ));
+        email.to_request_body(false).unwrap();
+
+        email.template_id("offer").text("Hi!");
+        let err = email.to_request_body(false).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Validation(ref errors)
+                if errors[..] == [ValidationError::InlineFieldNotApplicable(
+                    "text".to_owned()
+                )]
+        ));
+    }
+
+    #[test]
+    fn too_large() {
+        let mut email = message();
+        email.text("a".repeat(MAX_MESSAGE_SIZE));
+
+        let errors = email.validate().unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [ValidationError::TooLarge { size }] if size > MAX_MESSAGE_SIZE
+        ));
+    }
+}